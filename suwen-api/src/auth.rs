@@ -9,15 +9,58 @@ use sea_orm::{
     ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, IntoActiveModel, JoinType,
     QueryFilter, QueryOrder, QuerySelect, RelationTrait, TransactionTrait, TryIntoModel,
 };
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use suwen_config::CONFIG;
 
+static ENCODING_KEY: LazyLock<EncodingKey> = LazyLock::new(|| EncodingKey::from_secret(CONFIG.jwt_secret.as_bytes()));
+static DECODING_KEY: LazyLock<DecodingKey> = LazyLock::new(|| DecodingKey::from_secret(CONFIG.jwt_secret.as_bytes()));
+
+/// 使用 jwt_secret 签名的凭证，不同用途的凭证通过 aud 区分，无法互相冒用
+pub(crate) trait Token: Serialize + DeserializeOwned {
+    const AUDIENCE: &'static str;
+    /// 是否接受缺少 aud 的凭证
+    const ALLOW_MISSING_AUDIENCE: bool = false;
+
+    fn encode(&self) -> String {
+        #[derive(Serialize)]
+        struct WithAudience<'a, T> {
+            aud: &'static str,
+            #[serde(flatten)]
+            claims: &'a T,
+        }
+        let claims = WithAudience {
+            aud: Self::AUDIENCE,
+            claims: self,
+        };
+        jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &ENCODING_KEY).expect("Failed to encode JWT")
+    }
+
+    fn decode(token: &str) -> Result<Self> {
+        let mut validation = jsonwebtoken::Validation::default();
+        validation.set_audience(&[Self::AUDIENCE]);
+        if Self::ALLOW_MISSING_AUDIENCE {
+            validation.set_required_spec_claims(&["exp"]);
+        } else {
+            validation.set_required_spec_claims(&["exp", "aud"]);
+        }
+        let data = jsonwebtoken::decode::<Self>(token, &DECODING_KEY, &validation)?;
+        Ok(data.claims)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct Claims {
     pub id: i32,
     pub exp: usize,
 }
 
+impl Token for Claims {
+    const AUDIENCE: &'static str = "suwen:session";
+    // 引入 aud 之前签发的登录凭证没有 aud，继续有效直到过期，避免升级后所有用户被迫重新登录
+    const ALLOW_MISSING_AUDIENCE: bool = true;
+}
+
 impl Claims {
     pub fn of(me: suwen_entity::user::Model, ttl: usize) -> Self {
         Self {
@@ -25,19 +68,25 @@ impl Claims {
             exp: chrono::Utc::now().timestamp() as usize + ttl,
         }
     }
+}
 
-    pub fn encode(&self) -> String {
-        static ENCODING_KEY: LazyLock<EncodingKey> =
-            LazyLock::new(|| EncodingKey::from_secret(CONFIG.jwt_secret.as_bytes()));
-        let header = jsonwebtoken::Header::default();
-        jsonwebtoken::encode(&header, &self, &ENCODING_KEY).expect("Failed to encode JWT")
-    }
+/// 草稿预览链接携带的签名信息
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PreviewClaims {
+    pub slug: String,
+    pub exp: usize,
+}
 
-    pub fn decode(token: &str) -> Result<Self> {
-        static DECODING_KEY: LazyLock<DecodingKey> =
-            LazyLock::new(|| DecodingKey::from_secret(CONFIG.jwt_secret.as_bytes()));
-        let data = jsonwebtoken::decode::<Self>(token, &DECODING_KEY, &jsonwebtoken::Validation::default())?;
-        Ok(data.claims)
+impl Token for PreviewClaims {
+    const AUDIENCE: &'static str = "suwen:preview";
+}
+
+impl PreviewClaims {
+    pub fn of(slug: String, ttl: usize) -> Self {
+        Self {
+            slug,
+            exp: chrono::Utc::now().timestamp() as usize + ttl,
+        }
     }
 }
 
@@ -102,3 +151,32 @@ impl Identity {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(claims: serde_json::Value) -> String {
+        jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &ENCODING_KEY).unwrap()
+    }
+
+    #[test]
+    fn test_token_audience() {
+        let token = PreviewClaims::of("draft".to_owned(), 60).encode();
+        assert_eq!(PreviewClaims::decode(&token).unwrap().slug, "draft");
+
+        let exp = chrono::Utc::now().timestamp() + 60;
+        // 字段兼容的凭证也不能跨用途使用
+        let preview = sign(serde_json::json!({"id": 1, "slug": "draft", "exp": exp, "aud": PreviewClaims::AUDIENCE}));
+        assert!(Claims::decode(&preview).is_err());
+        let session = sign(serde_json::json!({"id": 1, "slug": "draft", "exp": exp, "aud": Claims::AUDIENCE}));
+        assert_eq!(Claims::decode(&session).unwrap().id, 1);
+        assert!(PreviewClaims::decode(&session).is_err());
+        // 缺少 aud 的旧登录凭证在过期前仍然有效，但不能用作预览凭证
+        let legacy = sign(serde_json::json!({"id": 1, "slug": "draft", "exp": exp}));
+        assert_eq!(Claims::decode(&legacy).unwrap().id, 1);
+        assert!(PreviewClaims::decode(&legacy).is_err());
+        let expired = sign(serde_json::json!({"id": 1, "exp": exp - 3600}));
+        assert!(Claims::decode(&expired).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use anyhow::{Context, Result, ensure};
+use chrono::{DateTime, Local};
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select};
+use suwen_entity::content_metadata;
+
+use crate::db::{ArticleByList, Page, Short};
+
+/// 基于 (排序列, id) 的游标，编码后对客户端不透明
+pub struct Cursor {
+    column: content_metadata::Column,
+    value: Option<i64>,
+    id: i32,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        hex::encode(format!(
+            "{}|{}|{}",
+            sort_name(self.column),
+            self.value.map(|v| v.to_string()).unwrap_or_default(),
+            self.id
+        ))
+    }
+
+    /// 解析游标，游标必须由相同的排序方式生成
+    pub fn decode(input: &str, column: content_metadata::Column) -> Result<Self> {
+        let decoded = String::from_utf8(hex::decode(input).context("Invalid cursor")?).context("Invalid cursor")?;
+        let mut parts = decoded.splitn(3, '|');
+        let (Some(sort), Some(value), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+            anyhow::bail!("Invalid cursor");
+        };
+        ensure!(sort == sort_name(column), "Cursor does not match the sort order");
+        Ok(Self {
+            column,
+            value: if value.is_empty() {
+                None
+            } else {
+                Some(value.parse().context("Invalid cursor")?)
+            },
+            id: id.parse().context("Invalid cursor")?,
+        })
+    }
+
+    /// 降序排列时 SQLite 将 NULL 排在最后，因此游标之后的记录为：
+    /// 排序值更小、排序值为 NULL，或排序值相同但 id 更小
+    fn condition(&self) -> Condition {
+        let id_before = content_metadata::Column::Id.lt(self.id);
+        match self.value {
+            None => Condition::all().add(self.column.is_null()).add(id_before),
+            Some(value) => {
+                let (lt, eq) = match self.column {
+                    content_metadata::Column::PublishedAt => {
+                        let time = DateTime::from_timestamp_nanos(value).with_timezone(&Local);
+                        (self.column.lt(time), self.column.eq(time))
+                    }
+                    _ => (self.column.lt(value), self.column.eq(value)),
+                };
+                Condition::any()
+                    .add(lt)
+                    .add(self.column.is_null())
+                    .add(Condition::all().add(eq).add(id_before))
+            }
+        }
+    }
+}
+
+pub(super) trait CursorItem {
+    fn cursor(&self, column: content_metadata::Column) -> Cursor;
+}
+
+impl CursorItem for ArticleByList {
+    fn cursor(&self, column: content_metadata::Column) -> Cursor {
+        let value = match column {
+            content_metadata::Column::ViewCount => Some(self.view_count as i64),
+            content_metadata::Column::CommentCount => Some(self.comment_count as i64),
+            _ => self.published_at.timestamp_nanos_opt(),
+        };
+        Cursor {
+            column,
+            value,
+            id: self.id,
+        }
+    }
+}
+
+impl CursorItem for Short {
+    fn cursor(&self, column: content_metadata::Column) -> Cursor {
+        let value = match column {
+            content_metadata::Column::ViewCount => Some(self.view_count as i64),
+            content_metadata::Column::CommentCount => Some(self.comment_count as i64),
+            _ => self.published_at.and_then(|t| t.timestamp_nanos_opt()),
+        };
+        Cursor {
+            column,
+            value,
+            id: self.id,
+        }
+    }
+}
+
+/// 为查询附加游标条件与排序，多取一条记录用于判断是否还有下一页
+pub(super) fn paginate<E: EntityTrait>(
+    select: Select<E>,
+    column: content_metadata::Column,
+    cursor: Option<&Cursor>,
+    limit: u64,
+) -> Select<E> {
+    let select = match cursor {
+        Some(cursor) => select.filter(cursor.condition()),
+        None => select,
+    };
+    select
+        .order_by_desc(column)
+        .order_by_desc(content_metadata::Column::Id)
+        .limit(limit.saturating_add(1))
+}
+
+pub(super) fn into_page<T: CursorItem>(mut items: Vec<T>, column: content_metadata::Column, limit: u64) -> Page<T> {
+    let has_more = items.len() as u64 > limit;
+    items.truncate(limit as usize);
+    let next_cursor = if has_more {
+        items.last().map(|item| item.cursor(column).encode())
+    } else {
+        None
+    };
+    Page {
+        items,
+        next_cursor,
+        has_more,
+    }
+}
+
+fn sort_name(column: content_metadata::Column) -> &'static str {
+    match column {
+        content_metadata::Column::ViewCount => "trending",
+        content_metadata::Column::CommentCount => "top-comments",
+        _ => "published",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use sea_orm::ActiveValue::Set;
+    use sea_orm::{DatabaseConnection, DbBackend, QueryTrait};
+
+    use super::*;
+    use crate::db::test_connection;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            column: content_metadata::Column::ViewCount,
+            value: Some(42),
+            id: 7,
+        };
+        let decoded = Cursor::decode(&cursor.encode(), content_metadata::Column::ViewCount).unwrap();
+        assert_eq!((decoded.value, decoded.id), (Some(42), 7));
+        let cursor = Cursor {
+            column: content_metadata::Column::PublishedAt,
+            value: None,
+            id: 3,
+        };
+        let decoded = Cursor::decode(&cursor.encode(), content_metadata::Column::PublishedAt).unwrap();
+        assert_eq!((decoded.value, decoded.id), (None, 3));
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        let column = content_metadata::Column::ViewCount;
+        assert!(Cursor::decode("not hex", column).is_err());
+        assert!(Cursor::decode(&hex::encode("trending|1"), column).is_err());
+        assert!(Cursor::decode(&hex::encode("trending|x|1"), column).is_err());
+        assert!(Cursor::decode(&hex::encode("trending|1|x"), column).is_err());
+        // 不同排序方式生成的游标不能混用
+        assert!(Cursor::decode(&hex::encode("published|1|1"), column).is_err());
+    }
+
+    async fn insert(conn: &DatabaseConnection, slug: &str, view_count: i32, published_at: Option<DateTime<Local>>) {
+        content_metadata::Entity::insert(content_metadata::ActiveModel {
+            slug: Set(slug.to_owned()),
+            content_hash: Set(slug.to_owned()),
+            cover_images: Set(Vec::new().into()),
+            tags: Set(Vec::new().into()),
+            content_type: Set("article".to_owned()),
+            view_count: Set(view_count),
+            published_at: Set(published_at),
+            ..Default::default()
+        })
+        .exec(conn)
+        .await
+        .unwrap();
+    }
+
+    /// 每页两条依次翻页，返回全部记录的 id
+    async fn page_ids(conn: &DatabaseConnection, column: content_metadata::Column) -> Vec<i32> {
+        let (mut cursor, mut ids) = (None, Vec::new());
+        loop {
+            let page = paginate(content_metadata::Entity::find(), column, cursor.as_ref(), 2)
+                .all(conn)
+                .await
+                .unwrap();
+            let has_more = page.len() > 2;
+            let page = page.into_iter().take(2).collect::<Vec<_>>();
+            ids.extend(page.iter().map(|model| model.id));
+            if !has_more {
+                return ids;
+            }
+            let last = page.last().unwrap();
+            let value = match column {
+                content_metadata::Column::ViewCount => Some(last.view_count as i64),
+                _ => last.published_at.and_then(|time| time.timestamp_nanos_opt()),
+            };
+            cursor = Some(Cursor {
+                column,
+                value,
+                id: last.id,
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_equal_sort_keys_break_ties_by_id() {
+        let conn = test_connection().await;
+        for idx in 0..5 {
+            insert(&conn, &format!("cursor-tie-{}", idx), 7, None).await;
+        }
+        assert_eq!(
+            page_ids(&conn, content_metadata::Column::ViewCount).await,
+            [5, 4, 3, 2, 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_published_at_ties() {
+        let conn = test_connection().await;
+        let second = Local.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap();
+        // 同一秒发布的多篇内容，以及更晚发布与未发布的内容
+        insert(&conn, "published-tie-0", 0, Some(second)).await;
+        insert(&conn, "published-later", 0, Some(second + chrono::Duration::hours(1))).await;
+        insert(&conn, "published-draft", 0, None).await;
+        for idx in 1..4 {
+            insert(&conn, &format!("published-tie-{}", idx), 0, Some(second)).await;
+        }
+        assert_eq!(
+            page_ids(&conn, content_metadata::Column::PublishedAt).await,
+            [2, 6, 5, 4, 1, 3]
+        );
+    }
+
+    #[test]
+    fn test_page_limit_overflow() {
+        let select = paginate(
+            content_metadata::Entity::find(),
+            content_metadata::Column::PublishedAt,
+            None,
+            u64::MAX,
+        );
+        assert!(
+            select
+                .build(DbBackend::Sqlite)
+                .to_string()
+                .contains(&u64::MAX.to_string())
+        );
+    }
+}
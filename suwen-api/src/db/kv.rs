@@ -6,11 +6,14 @@ use sea_orm::QuerySelect;
 use sea_orm::prelude::*;
 
 use crate::db;
+use crate::db::{ArticleByList, Lang};
 
 static KEY_LOCK: LazyLock<DashMap<String, Arc<tokio::sync::Mutex<()>>>> = LazyLock::new(DashMap::new);
 
 static SLUG_TO_ID: LazyLock<DashMap<String, i32>> = LazyLock::new(DashMap::new);
 
+static RELATED_BY_TAGS: LazyLock<DashMap<String, Vec<ArticleByList>>> = LazyLock::new(DashMap::new);
+
 fn template_key<T: ToString>(scope: &str, key: T) -> String {
     format!("suwen_{}_{}", scope, key.to_string())
 }
@@ -64,7 +67,11 @@ pub async fn get_metadata_id_for_slug(slug: &str, conn: &db::DatabaseConnection)
             let metadata_id = suwen_entity::content_metadata::Entity::find()
                 .select_only()
                 .column(suwen_entity::content_metadata::Column::Id)
-                .filter(suwen_entity::content_metadata::Column::Slug.eq(slug))
+                .filter(
+                    suwen_entity::content_metadata::Column::Slug
+                        .eq(slug)
+                        .and(suwen_entity::content_metadata::Column::DeletedAt.is_null()),
+                )
                 .into_tuple::<i32>()
                 .one(conn)
                 .await?
@@ -75,3 +82,41 @@ pub async fn get_metadata_id_for_slug(slug: &str, conn: &db::DatabaseConnection)
     )
     .await
 }
+
+/// 缓存的推荐数量，与接口单次返回的上限一致
+const RELATED_CACHE_LIMIT: u64 = 100;
+
+/// 每篇文章只缓存一份完整列表，读取时再按 limit 截断
+pub async fn get_related_articles_by_tags(
+    conn: &db::DatabaseConnection,
+    slug: &str,
+    lang: Lang,
+    limit: u64,
+) -> Result<Vec<ArticleByList>> {
+    let mut articles = with_key_lock(
+        "related_by_tags",
+        format!("{}_{}", slug, lang),
+        async |key: &str| Ok(RELATED_BY_TAGS.get(key).map(|v| v.value().clone())),
+        async move |key: &str| {
+            let articles = db::query::rank_related_articles_by_tags(conn, slug, lang, RELATED_CACHE_LIMIT).await?;
+            RELATED_BY_TAGS.insert(key.to_owned(), articles.clone());
+            Ok(articles)
+        },
+    )
+    .await?;
+    articles.truncate(limit.min(RELATED_CACHE_LIMIT) as usize);
+    Ok(articles)
+}
+
+pub fn invalidate_slug_cache(slug: &str) {
+    SLUG_TO_ID.remove(&template_key("metadata_id_for_slug", slug));
+}
+
+pub fn clear_slug_cache() {
+    SLUG_TO_ID.clear();
+}
+
+/// 标签的稀有度是全局统计量，任意文章的标签变化都可能影响所有文章的推荐结果，因此整体清空
+pub fn invalidate_related_cache() {
+    RELATED_BY_TAGS.clear();
+}
@@ -1,3 +1,4 @@
+mod cursor;
 mod kv;
 mod query;
 mod schema;
@@ -5,8 +6,12 @@ mod utils;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
+pub use cursor::Cursor;
 use dirs::config_dir;
-pub use kv::get_metadata_id_for_slug;
+pub use kv::{
+    clear_slug_cache, get_metadata_id_for_slug, get_related_articles_by_tags, invalidate_related_cache,
+    invalidate_slug_cache,
+};
 pub use query::*;
 pub use schema::*;
 pub use sea_orm::DatabaseConnection;
@@ -55,3 +60,11 @@ pub async fn database_connection() -> Result<DatabaseConnection> {
             .await?,
     ))
 }
+
+/// 测试使用的内存数据库，已执行全部迁移
+#[cfg(test)]
+pub(crate) async fn test_connection() -> DatabaseConnection {
+    let conn = Database::connect("sqlite::memory:").await.unwrap();
+    Migrator::up(&conn, None).await.unwrap();
+    conn
+}
@@ -1,21 +1,45 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{Context, Result, bail, ensure};
 use chrono::Datelike;
+use itertools::Itertools;
 use sea_orm::ActiveValue::{NotSet, Set};
+use sea_orm::sea_query::{OnConflict, Query, SimpleExpr};
 use sea_orm::{
-    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, JoinType, QueryFilter, QueryOrder, QuerySelect,
-    RelationTrait, TransactionTrait,
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait, JoinType, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait, TransactionTrait,
 };
+use suwen_config::CONFIG;
 use suwen_entity::*;
-use suwen_llm::generate_article_summary;
-use suwen_markdown::{Markdown, MarkdownChange};
-use suwen_migration::Expr;
+use suwen_llm::{
+    Embedding, cosine_similarity, generate_alt_text, generate_article_embedding, generate_article_summary,
+    generate_embedding,
+};
+use suwen_markdown::importer::ImportedComment;
+use suwen_markdown::{Markdown, MarkdownChange, StoredMedia};
+use suwen_migration::{Alias, Expr};
 
-use crate::db::schema::{Archive, ArticleByList, ArticleBySlug, Short, Site, SitemapUrl, TagWithCount};
+use crate::db::cursor::{Cursor, into_page, paginate};
+use crate::db::schema::{
+    Archive, ArticleByList, ArticleBySlug, ArticleFilter, CoverImage, Page, ScheduledContent, SearchMode, SeriesDetail,
+    SeriesLink, SeriesNavigation, SeriesWithCount, Short, Site, SitemapUrl, TagWithCount, TrashedContent,
+};
 use crate::db::utils::sha256_hash;
-use crate::db::{ArticleForRSS, Comment, Lang, get_metadata_id_for_slug};
+use crate::db::{
+    ArticleForRSS, Comment, Lang, clear_slug_cache, get_metadata_id_for_slug, invalidate_related_cache,
+    invalidate_slug_cache,
+};
 use crate::routes::IdentityInfo;
+use crate::scheduler::reschedule;
+
+const SHORT_COLUMNS: [content_metadata::Column; 6] = [
+    content_metadata::Column::Id,
+    content_metadata::Column::Slug,
+    content_metadata::Column::CoverImages,
+    content_metadata::Column::ViewCount,
+    content_metadata::Column::CommentCount,
+    content_metadata::Column::PublishedAt,
+];
 
 pub async fn init(conn: &DatabaseConnection) -> Result<()> {
     let txn = conn.begin().await?;
@@ -92,6 +116,14 @@ pub async fn init(conn: &DatabaseConnection) -> Result<()> {
     Ok(())
 }
 
+/// 发布时间晚于当前时间的内容视为定时发布，在到达发布时间前不公开；回收站中的内容同样不公开
+fn is_published() -> SimpleExpr {
+    content_metadata::Column::PublishedAt
+        .lte(chrono::Local::now())
+        .and(content_metadata::Column::DeletedAt.is_null())
+        .and(content_metadata::Column::UnpublishedAt.is_null())
+}
+
 pub async fn get_site(conn: &impl ConnectionTrait) -> Result<Option<Site>> {
     Ok(site::Entity::find()
         .select_only()
@@ -117,11 +149,14 @@ pub async fn get_articles(
     lang: Lang,
     sort_column: content_metadata::Column,
     published: Option<bool>,
+    filter: &ArticleFilter,
+    cursor: Option<&Cursor>,
     limit: u64,
-) -> Result<Vec<ArticleByList>> {
+) -> Result<Page<ArticleByList>> {
     let query = content_metadata::Entity::find()
         .select_only()
         .columns([
+            content_metadata::Column::Id,
             content_metadata::Column::Slug,
             content_metadata::Column::CoverImages,
             content_metadata::Column::Tags,
@@ -135,21 +170,64 @@ pub async fn get_articles(
         .inner_join(content::Entity)
         .filter(
             content_metadata::Column::ContentType
-                .eq("article")
+                .eq(filter.content_type.unwrap_or("article"))
                 .and(content::Column::LangCode.eq(lang.to_string())),
         )
-        .order_by_desc(sort_column)
-        .limit(limit);
-    let query = if let Some(published) = published {
-        query.filter(if published {
-            content_metadata::Column::PublishedAt.is_not_null()
-        } else {
-            content_metadata::Column::PublishedAt.is_null()
-        })
+        .filter(filter_condition(filter));
+    // 默认只返回已发布的内容
+    let query = query.filter(if published.unwrap_or(true) {
+        is_published()
     } else {
-        query
-    };
-    Ok(query.into_model::<ArticleByList>().all(conn).await?)
+        content_metadata::Column::DeletedAt
+            .is_null()
+            .and(content_metadata::Column::PublishedAt.is_null().or(is_published().not()))
+    });
+    let mut articles = paginate(query, sort_column, cursor, limit)
+        .into_model::<ArticleByList>()
+        .all(conn)
+        .await?;
+    fill_covers(&mut articles, conn).await?;
+    Ok(into_page(articles, sort_column, limit))
+}
+
+fn filter_condition(filter: &ArticleFilter) -> Condition {
+    let mut condition = Condition::all();
+    if !filter.tags.is_empty() {
+        let mut tagged = Query::select();
+        tagged
+            .column(content_metadata_tag::Column::ContentMetadataId)
+            .from(content_metadata_tag::Entity)
+            .and_where(content_metadata_tag::Column::TagName.is_in(filter.tags.clone()));
+        if filter.match_all_tags {
+            let tag_count = filter.tags.iter().unique().count() as i32;
+            tagged
+                .group_by_col(content_metadata_tag::Column::ContentMetadataId)
+                .and_having(
+                    Expr::col(content_metadata_tag::Column::TagName)
+                        .count_distinct()
+                        .eq(tag_count),
+                );
+        }
+        condition = condition.add(content_metadata::Column::Id.in_subquery(tagged.to_owned()));
+    }
+    if !filter.exclude_tags.is_empty() {
+        condition = condition.add(
+            content_metadata::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(content_metadata_tag::Column::ContentMetadataId)
+                    .from(content_metadata_tag::Entity)
+                    .and_where(content_metadata_tag::Column::TagName.is_in(filter.exclude_tags.clone()))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(from) = filter.from {
+        condition = condition.add(content_metadata::Column::PublishedAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        condition = condition.add(content_metadata::Column::PublishedAt.lte(to));
+    }
+    condition
 }
 
 pub async fn get_rss_articles(conn: &DatabaseConnection, lang: Lang, limit: u64) -> Result<Vec<ArticleForRSS>> {
@@ -170,7 +248,7 @@ pub async fn get_rss_articles(conn: &DatabaseConnection, lang: Lang, limit: u64)
             content_metadata::Column::ContentType
                 .eq("article")
                 .and(content::Column::LangCode.eq(lang.to_string()))
-                .and(content_metadata::Column::PublishedAt.is_not_null()),
+                .and(is_published()),
         )
         .order_by_desc(content_metadata::Column::PublishedAt)
         .limit(limit)
@@ -184,11 +262,12 @@ pub async fn get_shorts(
     lang: Lang,
     sort_column: content_metadata::Column,
     published: Option<bool>,
-    limit: u32,
-) -> Result<Vec<Short>> {
+    cursor: Option<&Cursor>,
+    limit: u64,
+) -> Result<Page<Short>> {
     let query = content_metadata::Entity::find()
         .select_only()
-        .columns([content_metadata::Column::Slug, content_metadata::Column::CoverImages])
+        .columns(SHORT_COLUMNS)
         .column_as(content::Column::Title, "title")
         .column_as(content::Column::OriginalText, "content")
         .column_as(content::Column::RenderedHtml, "rendered_html")
@@ -197,25 +276,27 @@ pub async fn get_shorts(
             content_metadata::Column::ContentType
                 .eq("gallery")
                 .and(content::Column::LangCode.eq(lang.to_string())),
-        )
-        .order_by_desc(sort_column)
-        .limit(limit as u64);
-    let query = if let Some(published) = published {
-        query.filter(if published {
-            content_metadata::Column::PublishedAt.is_not_null()
-        } else {
-            content_metadata::Column::PublishedAt.is_null()
-        })
+        );
+    // 默认只返回已发布的内容
+    let query = query.filter(if published.unwrap_or(true) {
+        is_published()
     } else {
-        query
-    };
-    Ok(query.into_model::<Short>().all(conn).await?)
+        content_metadata::Column::DeletedAt
+            .is_null()
+            .and(content_metadata::Column::PublishedAt.is_null().or(is_published().not()))
+    });
+    let mut shorts = paginate(query, sort_column, cursor, limit)
+        .into_model::<Short>()
+        .all(conn)
+        .await?;
+    fill_covers(&mut shorts, conn).await?;
+    Ok(into_page(shorts, sort_column, limit))
 }
 
 pub async fn get_short_by_slug(conn: &DatabaseConnection, slug: &str, lang: Lang) -> Result<Option<Short>> {
-    Ok(content_metadata::Entity::find()
+    let Some(mut short) = content_metadata::Entity::find()
         .select_only()
-        .columns([content_metadata::Column::Slug, content_metadata::Column::CoverImages])
+        .columns(SHORT_COLUMNS)
         .column_as(content::Column::Title, "title")
         .column_as(content::Column::OriginalText, "content")
         .column_as(content::Column::RenderedHtml, "rendered_html")
@@ -225,15 +306,155 @@ pub async fn get_short_by_slug(conn: &DatabaseConnection, slug: &str, lang: Lang
                 .eq("gallery")
                 .and(content::Column::LangCode.eq(lang.to_string()))
                 .and(content_metadata::Column::Slug.eq(slug))
-                .and(content_metadata::Column::PublishedAt.is_not_null()),
+                .and(is_published()),
         )
         .into_model::<Short>()
         .one(conn)
-        .await?)
+        .await?
+    else {
+        return Ok(None);
+    };
+    fill_covers(std::slice::from_mut(&mut short), conn).await?;
+    Ok(Some(short))
+}
+
+/// 带有封面图片的列表项
+trait WithCovers {
+    fn cover_urls(&self) -> &[String];
+
+    fn set_covers(&mut self, covers: Vec<CoverImage>);
+}
+
+impl WithCovers for ArticleByList {
+    fn cover_urls(&self) -> &[String] {
+        &self.cover_images.0
+    }
+
+    fn set_covers(&mut self, covers: Vec<CoverImage>) {
+        self.covers = covers;
+    }
+}
+
+impl WithCovers for Short {
+    fn cover_urls(&self) -> &[String] {
+        &self.cover_images.0
+    }
+
+    fn set_covers(&mut self, covers: Vec<CoverImage>) {
+        self.covers = covers;
+    }
+}
+
+/// 根据已记录的媒体信息填充封面图片
+async fn fill_covers<T: WithCovers>(items: &mut [T], conn: &impl ConnectionTrait) -> Result<()> {
+    let media = get_media(items.iter().flat_map(|item| item.cover_urls().iter().cloned()), conn).await?;
+    for item in items.iter_mut() {
+        let covers = item
+            .cover_urls()
+            .iter()
+            .map(|url| CoverImage::new(url.clone(), media.get(url)))
+            .collect();
+        item.set_covers(covers);
+    }
+    Ok(())
+}
+
+/// 记录上传的媒体，同一地址重复上传时以最新的结果为准
+pub async fn save_media(media: &[StoredMedia], conn: &impl ConnectionTrait) -> Result<()> {
+    if media.is_empty() {
+        return Ok(());
+    }
+    media::Entity::insert_many(media.iter().map(|m| media::ActiveModel {
+        url: Set(m.url.clone()),
+        width: Set(m.width.map(|w| w as i32)),
+        height: Set(m.height.map(|h| h as i32)),
+        variants: Set(m.variants.clone().into()),
+        updated_at: Set(chrono::Local::now()),
+        placeholder: Set(m.placeholder.clone()),
+        hash: Set(Some(m.hash.clone())),
+        poster: Set(m.poster.clone()),
+        duration: Set(m.duration.map(|d| d as i32)),
+    }))
+    .on_conflict(
+        OnConflict::column(media::Column::Url)
+            .update_columns([
+                media::Column::Width,
+                media::Column::Height,
+                media::Column::Variants,
+                media::Column::UpdatedAt,
+                media::Column::Placeholder,
+                media::Column::Hash,
+                media::Column::Poster,
+                media::Column::Duration,
+            ])
+            .to_owned(),
+    )
+    .exec(conn)
+    .await?;
+    Ok(())
+}
+
+/// 查询已记录的媒体信息，以地址为键
+pub async fn get_media(
+    urls: impl IntoIterator<Item = String>,
+    conn: &impl ConnectionTrait,
+) -> Result<HashMap<String, media::Model>> {
+    let urls = urls.into_iter().unique().collect::<Vec<_>>();
+    if urls.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(media::Entity::find()
+        .filter(media::Column::Url.is_in(urls))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|m| (m.url.clone(), m))
+        .collect())
+}
+
+/// 收集可能引用媒体文件的全部文本，包括回收站中的内容与预览
+pub async fn get_media_references(conn: &DatabaseConnection) -> Result<Vec<String>> {
+    let mut texts = Vec::new();
+    let contents: Vec<(String, Option<String>)> = content::Entity::find()
+        .select_only()
+        .columns([content::Column::OriginalText, content::Column::RenderedHtml])
+        .into_tuple()
+        .all(conn)
+        .await?;
+    for (original_text, rendered_html) in contents {
+        texts.push(original_text);
+        texts.extend(rendered_html);
+    }
+    let covers: Vec<VecString> = content_metadata::Entity::find()
+        .select_only()
+        .column(content_metadata::Column::CoverImages)
+        .into_tuple()
+        .all(conn)
+        .await?;
+    texts.extend(covers.into_iter().flat_map(|covers| covers.0));
+    for preview in content_preview::Entity::find().all(conn).await? {
+        texts.extend(preview.rendered_html);
+        texts.extend(preview.cover_images.0);
+    }
+    for site in site::Entity::find().all(conn).await? {
+        texts.push(serde_json::to_string(&site.tabs)?);
+        texts.push(serde_json::to_string(&site.related_links)?);
+    }
+    Ok(texts)
+}
+
+pub async fn delete_media(urls: &[String], conn: &DatabaseConnection) -> Result<()> {
+    for chunk in urls.chunks(500) {
+        media::Entity::delete_many()
+            .filter(media::Column::Url.is_in(chunk))
+            .exec(conn)
+            .await?;
+    }
+    Ok(())
 }
 
 pub async fn get_article_by_slug(conn: &DatabaseConnection, slug: &str, lang: Lang) -> Result<Option<ArticleBySlug>> {
-    Ok(content_metadata::Entity::find()
+    let article = content_metadata::Entity::find()
         .select_only()
         .columns([
             content_metadata::Column::Id,
@@ -243,6 +464,7 @@ pub async fn get_article_by_slug(conn: &DatabaseConnection, slug: &str, lang: La
             content_metadata::Column::CommentCount,
             content_metadata::Column::LikeCount,
             content_metadata::Column::PublishedAt,
+            content_metadata::Column::SeriesId,
         ])
         .column_as(content::Column::Title, "title")
         .column_as(content::Column::RenderedHtml, "rendered_html")
@@ -255,16 +477,136 @@ pub async fn get_article_by_slug(conn: &DatabaseConnection, slug: &str, lang: La
                 .eq("article")
                 .and(content::Column::LangCode.eq(lang.to_string()))
                 .and(content_metadata::Column::Slug.eq(slug))
-                .and(content_metadata::Column::PublishedAt.is_not_null()),
+                .and(is_published()),
         )
         .into_model::<ArticleBySlug>()
         .one(conn)
+        .await?;
+    let Some(mut article) = article else {
+        return Ok(None);
+    };
+    if let Some(series_id) = article.series_id {
+        article.series = get_series_navigation(conn, series_id, article.id, lang).await?;
+    }
+    Ok(Some(article))
+}
+
+async fn get_series_navigation(
+    conn: &DatabaseConnection,
+    series_id: i32,
+    article_id: i32,
+    lang: Lang,
+) -> Result<Option<SeriesNavigation>> {
+    let Some(series) = series::Entity::find_by_id(series_id).one(conn).await? else {
+        return Ok(None);
+    };
+    let mut parts = series_parts_query(series_id, lang)
+        .select_only()
+        .columns([content_metadata::Column::Id, content_metadata::Column::Slug])
+        .column_as(content::Column::Title, "title")
+        .into_model::<SeriesLink>()
+        .all(conn)
+        .await?;
+    let Some(index) = parts.iter().position(|part| part.id == article_id) else {
+        return Ok(None);
+    };
+    let total = parts.len();
+    let next = (index + 1 < total).then(|| parts.remove(index + 1));
+    let prev = (index > 0).then(|| parts.remove(index - 1));
+    Ok(Some(SeriesNavigation {
+        name: series.name,
+        position: index + 1,
+        total,
+        prev,
+        next,
+    }))
+}
+
+/// 系列中已发布的文章，按照系列内顺序排列
+fn series_parts_query(series_id: i32, lang: Lang) -> sea_orm::Select<content_metadata::Entity> {
+    content_metadata::Entity::find()
+        .inner_join(content::Entity)
+        .filter(
+            content_metadata::Column::SeriesId
+                .eq(series_id)
+                .and(content_metadata::Column::ContentType.eq("article"))
+                .and(content::Column::LangCode.eq(lang.to_string()))
+                .and(is_published()),
+        )
+        .order_by_asc(content_metadata::Column::SeriesOrder)
+        .order_by_asc(content_metadata::Column::PublishedAt)
+}
+
+pub async fn get_series_list(conn: &DatabaseConnection) -> Result<Vec<SeriesWithCount>> {
+    Ok(series::Entity::find()
+        .select_only()
+        .column(series::Column::Name)
+        .column_as(content_metadata::Column::Id.count(), "article_count")
+        .column_as(content_metadata::Column::PublishedAt.max(), "updated_at")
+        .inner_join(content_metadata::Entity)
+        .filter(content_metadata::Column::ContentType.eq("article").and(is_published()))
+        .group_by(series::Column::Id)
+        .order_by_desc(Expr::col(Alias::new("updated_at")))
+        .into_model::<SeriesWithCount>()
+        .all(conn)
+        .await?)
+}
+
+pub async fn get_series_by_name(conn: &DatabaseConnection, name: &str, lang: Lang) -> Result<Option<SeriesDetail>> {
+    let Some(series) = series::Entity::find()
+        .filter(series::Column::Name.eq(name))
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let mut articles = series_parts_query(series.id, lang)
+        .select_only()
+        .columns([
+            content_metadata::Column::Id,
+            content_metadata::Column::Slug,
+            content_metadata::Column::CoverImages,
+            content_metadata::Column::Tags,
+            content_metadata::Column::ViewCount,
+            content_metadata::Column::CommentCount,
+            content_metadata::Column::PublishedAt,
+        ])
+        .column_as(content::Column::Title, "title")
+        .column_as(content::Column::Intro, "intro")
+        .column_as(content::Column::Summary, "summary")
+        .into_model::<ArticleByList>()
+        .all(conn)
+        .await?;
+    if articles.is_empty() {
+        return Ok(None);
+    }
+    fill_covers(&mut articles, conn).await?;
+    Ok(Some(SeriesDetail {
+        name: series.name,
+        articles,
+    }))
+}
+
+/// 查询旧 slug 跳转到的当前 slug
+pub async fn get_slug_redirect(conn: &DatabaseConnection, slug: &str, content_type: &str) -> Result<Option<String>> {
+    Ok(slug_redirect::Entity::find()
+        .select_only()
+        .column(content_metadata::Column::Slug)
+        .inner_join(content_metadata::Entity)
+        .filter(
+            slug_redirect::Column::OldSlug
+                .eq(slug)
+                .and(content_metadata::Column::ContentType.eq(content_type))
+                .and(is_published()),
+        )
+        .into_tuple::<String>()
+        .one(conn)
         .await?)
 }
 
 pub async fn increase_article_view_count(conn: &DatabaseConnection, slug: &str) -> Result<i32> {
     let metadata = content_metadata::Entity::update_many()
-        .filter(content_metadata::Column::Slug.eq(slug))
+        .filter(content_metadata::Column::Slug.eq(slug).and(is_published()))
         .col_expr(
             content_metadata::Column::ViewCount,
             Expr::col(content_metadata::Column::ViewCount).add(1),
@@ -280,6 +622,8 @@ pub async fn get_tags_with_count(conn: &DatabaseConnection) -> Result<Vec<TagWit
         .select_only()
         .column(content_metadata_tag::Column::TagName)
         .column_as(content_metadata_tag::Column::ContentMetadataId.count(), "count")
+        .inner_join(content_metadata::Entity)
+        .filter(is_published())
         .group_by(content_metadata_tag::Column::TagName)
         .into_model::<TagWithCount>()
         .all(conn)
@@ -295,7 +639,8 @@ pub async fn get_archives_grouped_by_year(conn: &DatabaseConnection, lang: Lang)
         .filter(
             content::Column::LangCode
                 .eq(lang.to_string())
-                .and(content_metadata::Column::ContentType.eq("article")),
+                .and(content_metadata::Column::ContentType.eq("article"))
+                .and(is_published()),
         )
         .order_by_desc(content_metadata::Column::PublishedAt)
         .into_model::<Archive>()
@@ -316,12 +661,14 @@ pub async fn get_articles_by_tag(
     tag_name: &str,
     lang: Lang,
     sort_column: content_metadata::Column,
+    cursor: Option<&Cursor>,
     limit: u64,
-) -> Result<Vec<ArticleByList>> {
-    Ok(content_metadata_tag::Entity::find()
+) -> Result<Page<ArticleByList>> {
+    let query = content_metadata_tag::Entity::find()
         .select_only()
         .column(content_metadata_tag::Column::TagName)
         .columns([
+            content_metadata::Column::Id,
             content_metadata::Column::Slug,
             content_metadata::Column::CoverImages,
             content_metadata::Column::Tags,
@@ -339,20 +686,375 @@ pub async fn get_articles_by_tag(
                 content_metadata::Column::ContentType
                     .eq("article")
                     .and(content::Column::LangCode.eq(lang.to_string()))
-                    .and(content_metadata::Column::PublishedAt.is_not_null()),
+                    .and(is_published()),
             ),
+        );
+    let mut articles = paginate(query, sort_column, cursor, limit)
+        .into_model::<ArticleByList>()
+        .all(conn)
+        .await?;
+    fill_covers(&mut articles, conn).await?;
+    Ok(into_page(articles, sort_column, limit))
+}
+
+pub async fn get_related_articles(
+    conn: &DatabaseConnection,
+    slug: &str,
+    lang: Lang,
+    limit: u64,
+) -> Result<Vec<ArticleByList>> {
+    let metadata_id = get_metadata_id_for_slug(slug, conn).await?;
+    let Some(target) = content_embedding::Entity::find_by_id(metadata_id).one(conn).await? else {
+        return Ok(vec![]);
+    };
+    let ranked = rank_by_embedding(conn, &target.model, &target.vector.0, Some(metadata_id), limit).await?;
+    get_articles_by_ids(conn, ranked, lang).await
+}
+
+/// 根据共同标签计算相关文章：标签按稀有度（IDF）加权后计算 Jaccard 相似度，并对较新的文章略微加权
+pub(super) async fn rank_related_articles_by_tags(
+    conn: &DatabaseConnection,
+    slug: &str,
+    lang: Lang,
+    limit: u64,
+) -> Result<Vec<ArticleByList>> {
+    let metadata_id = get_metadata_id_for_slug(slug, conn).await?;
+    let rows = content_metadata_tag::Entity::find()
+        .select_only()
+        .column(content_metadata_tag::Column::ContentMetadataId)
+        .column(content_metadata_tag::Column::TagName)
+        .column(content_metadata::Column::PublishedAt)
+        .inner_join(content_metadata::Entity)
+        .filter(content_metadata::Column::ContentType.eq("article").and(is_published()))
+        .into_tuple::<(i32, String, chrono::DateTime<chrono::Local>)>()
+        .all(conn)
+        .await?;
+    let mut article_tags: HashMap<i32, (Vec<String>, chrono::DateTime<chrono::Local>)> = HashMap::new();
+    let mut document_frequency: HashMap<String, usize> = HashMap::new();
+    for (id, tag, published_at) in rows {
+        *document_frequency.entry(tag.clone()).or_default() += 1;
+        article_tags
+            .entry(id)
+            .or_insert_with(|| (Vec::new(), published_at))
+            .0
+            .push(tag);
+    }
+    let Some((target_tags, _)) = article_tags.get(&metadata_id) else {
+        return Ok(vec![]);
+    };
+    let total = article_tags.len() as f64;
+    let idf = |tag: &String| (1.0 + total / document_frequency.get(tag).copied().unwrap_or(1) as f64).ln();
+    let now = chrono::Local::now();
+    let mut scored = article_tags
+        .iter()
+        .filter(|(id, _)| **id != metadata_id)
+        .filter_map(|(id, (tags, published_at))| {
+            let shared = tags.iter().filter(|t| target_tags.contains(t)).map(idf).sum::<f64>();
+            if shared == 0.0 {
+                return None;
+            }
+            let union = target_tags
+                .iter()
+                .chain(tags.iter().filter(|t| !target_tags.contains(t)))
+                .map(idf)
+                .sum::<f64>();
+            let age_days = (now - *published_at).num_days().max(0) as f64;
+            let recency = (-age_days / 365.0).exp();
+            Some((*id, shared / union * (0.8 + 0.2 * recency)))
+        })
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let ranked = scored.into_iter().take(limit as usize).map(|(id, _)| id).collect();
+    get_articles_by_ids(conn, ranked, lang).await
+}
+
+pub async fn search_articles(
+    conn: &DatabaseConnection,
+    keyword: &str,
+    mode: SearchMode,
+    lang: Lang,
+    limit: u64,
+) -> Result<Vec<ArticleByList>> {
+    match mode {
+        SearchMode::Keyword => {
+            let pattern = format!("%{}%", keyword);
+            let mut articles = content_metadata::Entity::find()
+                .select_only()
+                .columns([
+                    content_metadata::Column::Id,
+                    content_metadata::Column::Slug,
+                    content_metadata::Column::CoverImages,
+                    content_metadata::Column::Tags,
+                    content_metadata::Column::ViewCount,
+                    content_metadata::Column::CommentCount,
+                    content_metadata::Column::PublishedAt,
+                ])
+                .column_as(content::Column::Title, "title")
+                .column_as(content::Column::Intro, "intro")
+                .column_as(content::Column::Summary, "summary")
+                .inner_join(content::Entity)
+                .filter(
+                    content_metadata::Column::ContentType
+                        .eq("article")
+                        .and(content::Column::LangCode.eq(lang.to_string()))
+                        .and(is_published())
+                        .and(
+                            content::Column::Title
+                                .like(&pattern)
+                                .or(content::Column::OriginalText.like(&pattern)),
+                        ),
+                )
+                .order_by_desc(content_metadata::Column::PublishedAt)
+                .limit(limit)
+                .into_model::<ArticleByList>()
+                .all(conn)
+                .await?;
+            fill_covers(&mut articles, conn).await?;
+            Ok(articles)
+        }
+        SearchMode::Semantic => {
+            let embedding = generate_embedding(keyword).await?;
+            let ranked = rank_by_embedding(conn, &embedding.model, &embedding.vector, None, limit).await?;
+            get_articles_by_ids(conn, ranked, lang).await
+        }
+    }
+}
+
+/// 计算与给定向量最相似的已发布文章，仅比较同一模型生成的向量
+async fn rank_by_embedding(
+    conn: &DatabaseConnection,
+    model: &str,
+    vector: &[f32],
+    exclude_id: Option<i32>,
+    limit: u64,
+) -> Result<Vec<i32>> {
+    let candidates = content_embedding::Entity::find()
+        .select_only()
+        .columns([
+            content_embedding::Column::ContentMetadataId,
+            content_embedding::Column::Vector,
+        ])
+        .inner_join(content_metadata::Entity)
+        .filter(
+            content_embedding::Column::Model
+                .eq(model)
+                .and(content_metadata::Column::ContentType.eq("article"))
+                .and(is_published()),
+        )
+        .into_tuple::<(i32, Vector)>()
+        .all(conn)
+        .await?;
+    let mut scored = candidates
+        .into_iter()
+        .filter(|(id, _)| Some(*id) != exclude_id)
+        .map(|(id, candidate)| (id, cosine_similarity(vector, &candidate.0)))
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Ok(scored.into_iter().take(limit as usize).map(|(id, _)| id).collect())
+}
+
+/// 按照给定 id 的顺序返回文章列表
+async fn get_articles_by_ids(conn: &DatabaseConnection, ids: Vec<i32>, lang: Lang) -> Result<Vec<ArticleByList>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut articles = content_metadata::Entity::find()
+        .select_only()
+        .columns([
+            content_metadata::Column::Id,
+            content_metadata::Column::Slug,
+            content_metadata::Column::CoverImages,
+            content_metadata::Column::Tags,
+            content_metadata::Column::ViewCount,
+            content_metadata::Column::CommentCount,
+            content_metadata::Column::PublishedAt,
+        ])
+        .column_as(content::Column::Title, "title")
+        .column_as(content::Column::Intro, "intro")
+        .column_as(content::Column::Summary, "summary")
+        .inner_join(content::Entity)
+        .filter(
+            content_metadata::Column::Id
+                .is_in(ids.clone())
+                .and(content::Column::LangCode.eq(lang.to_string())),
         )
-        .order_by_desc(sort_column)
-        .limit(limit)
         .into_model::<ArticleByList>()
         .all(conn)
+        .await?
+        .into_iter()
+        .map(|article| (article.id, article))
+        .collect::<HashMap<_, _>>();
+    let mut articles = ids
+        .into_iter()
+        .filter_map(|id| articles.remove(&id))
+        .collect::<Vec<_>>();
+    fill_covers(&mut articles, conn).await?;
+    Ok(articles)
+}
+
+/// 获取尚未到达发布时间的内容，按发布时间升序排列
+pub async fn get_scheduled_contents(conn: &DatabaseConnection, lang: Lang) -> Result<Vec<ScheduledContent>> {
+    Ok(scheduled_contents_query(lang)
+        .filter(content_metadata::Column::PublishedAt.gt(chrono::Local::now()))
+        .into_model::<ScheduledContent>()
+        .all(conn)
         .await?)
 }
 
+/// 获取发布时间位于 (from, to] 区间内的内容
+pub async fn get_contents_published_between(
+    conn: &DatabaseConnection,
+    lang: Lang,
+    from: chrono::DateTime<chrono::Local>,
+    to: chrono::DateTime<chrono::Local>,
+) -> Result<Vec<ScheduledContent>> {
+    Ok(scheduled_contents_query(lang)
+        .filter(
+            content_metadata::Column::PublishedAt
+                .gt(from)
+                .and(content_metadata::Column::PublishedAt.lte(to)),
+        )
+        .into_model::<ScheduledContent>()
+        .all(conn)
+        .await?)
+}
+
+/// 定时发布上一次检查到的时间，用于在重启后补发停机期间到期内容的通知
+pub async fn get_publish_checked_at(conn: &DatabaseConnection) -> Result<Option<chrono::DateTime<chrono::Local>>> {
+    Ok(site::Entity::find()
+        .select_only()
+        .column(site::Column::PublishCheckedAt)
+        .into_tuple::<Option<chrono::DateTime<chrono::Local>>>()
+        .one(conn)
+        .await?
+        .flatten())
+}
+
+pub async fn set_publish_checked_at(
+    conn: &DatabaseConnection,
+    checked_at: chrono::DateTime<chrono::Local>,
+) -> Result<()> {
+    site::Entity::update_many()
+        .col_expr(site::Column::PublishCheckedAt, Expr::value(checked_at))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_next_scheduled_at(conn: &DatabaseConnection) -> Result<Option<chrono::DateTime<chrono::Local>>> {
+    Ok(content_metadata::Entity::find()
+        .select_only()
+        .column(content_metadata::Column::PublishedAt)
+        .filter(
+            content_metadata::Column::PublishedAt
+                .gt(chrono::Local::now())
+                .and(content_metadata::Column::DeletedAt.is_null())
+                .and(content_metadata::Column::UnpublishedAt.is_null()),
+        )
+        .order_by_asc(content_metadata::Column::PublishedAt)
+        .into_tuple::<chrono::DateTime<chrono::Local>>()
+        .one(conn)
+        .await?)
+}
+
+fn scheduled_contents_query(lang: Lang) -> sea_orm::Select<content_metadata::Entity> {
+    content_metadata::Entity::find()
+        .select_only()
+        .columns([
+            content_metadata::Column::Slug,
+            content_metadata::Column::ContentType,
+            content_metadata::Column::PublishedAt,
+        ])
+        .column_as(content::Column::Title, "title")
+        .inner_join(content::Entity)
+        .filter(
+            content::Column::LangCode
+                .eq(lang.to_string())
+                .and(content_metadata::Column::DeletedAt.is_null())
+                .and(content_metadata::Column::UnpublishedAt.is_null()),
+        )
+        .order_by_asc(content_metadata::Column::PublishedAt)
+}
+
+/// 获取回收站中的内容，按删除时间倒序排列
+pub async fn get_trashed_contents(conn: &DatabaseConnection, lang: Lang) -> Result<Vec<TrashedContent>> {
+    let mut contents = content_metadata::Entity::find()
+        .select_only()
+        .columns([
+            content_metadata::Column::Slug,
+            content_metadata::Column::ContentType,
+            content_metadata::Column::ViewCount,
+            content_metadata::Column::CommentCount,
+            content_metadata::Column::LikeCount,
+            content_metadata::Column::DeletedAt,
+        ])
+        .column_as(content::Column::Title, "title")
+        .inner_join(content::Entity)
+        .filter(
+            content::Column::LangCode
+                .eq(lang.to_string())
+                .and(content_metadata::Column::DeletedAt.is_not_null()),
+        )
+        .order_by_desc(content_metadata::Column::DeletedAt)
+        .into_model::<TrashedContent>()
+        .all(conn)
+        .await?;
+    let retention = chrono::Duration::days(CONFIG.trash_retention_days as i64);
+    for content in &mut contents {
+        content.purge_at = content.deleted_at + retention;
+    }
+    Ok(contents)
+}
+
+/// 彻底删除超过保留期限的内容，返回删除的数量
+pub async fn purge_expired_trash(conn: &DatabaseConnection) -> Result<u64> {
+    let deadline = chrono::Local::now() - chrono::Duration::days(CONFIG.trash_retention_days as i64);
+    let result = content_metadata::Entity::delete_many()
+        .filter(content_metadata::Column::DeletedAt.lt(deadline))
+        .exec(conn)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+/// 将内容移入回收站，保留评论、点赞与浏览数据
+async fn move_to_trash(condition: SimpleExpr, conn: &DatabaseConnection) -> Result<u64> {
+    let result = content_metadata::Entity::update_many()
+        .filter(condition.and(content_metadata::Column::DeletedAt.is_null()))
+        .col_expr(content_metadata::Column::DeletedAt, Expr::value(chrono::Local::now()))
+        .exec(conn)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+/// 数据库中已同步的 slug，包括未删除的内容与草稿预览
+pub async fn get_synced_slugs(conn: &DatabaseConnection) -> Result<HashSet<String>> {
+    let mut slugs = content_metadata::Entity::find()
+        .select_only()
+        .column(content_metadata::Column::Slug)
+        .filter(content_metadata::Column::DeletedAt.is_null())
+        .into_tuple::<String>()
+        .all(conn)
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    slugs.extend(
+        content_preview::Entity::find()
+            .select_only()
+            .column(content_preview::Column::Slug)
+            .into_tuple::<String>()
+            .all(conn)
+            .await?,
+    );
+    Ok(slugs)
+}
+
 pub async fn handle_markdown_change(conn: &DatabaseConnection, change: MarkdownChange) -> Result<()> {
     match change {
         MarkdownChange::Upsert(mut markdown) => {
             let slug = markdown.slug().to_owned();
+            let aliases = markdown.aliases().to_vec();
+            save_media(markdown.media(), conn).await?;
+            content_preview::Entity::delete_by_id(&slug).exec(conn).await?;
             let cover_images = markdown.extract_images()?;
             markdown.strip_images()?;
             markdown.auto_format()?;
@@ -365,14 +1067,74 @@ pub async fn handle_markdown_change(conn: &DatabaseConnection, change: MarkdownC
                 && content_hash == metadata.content_hash
             {
                 info!("Content hash unchanged, skipping update: {}", &slug);
+                save_aliases(metadata.id, &slug, &aliases, conn).await?;
+                if metadata.deleted_at.is_some() {
+                    info!("Restoring article from trash: {}", &slug);
+                    content_metadata::Entity::update_many()
+                        .filter(content_metadata::Column::Id.eq(metadata.id))
+                        .col_expr(
+                            content_metadata::Column::DeletedAt,
+                            Expr::value(Option::<chrono::DateTime<chrono::Local>>::None),
+                        )
+                        .exec(conn)
+                        .await?;
+                    invalidate_related_cache();
+                    reschedule();
+                }
+                if metadata.unpublished_at.is_some() {
+                    info!("Republishing article: {}", &slug);
+                    content_metadata::Entity::update_many()
+                        .filter(content_metadata::Column::Id.eq(metadata.id))
+                        .col_expr(
+                            content_metadata::Column::UnpublishedAt,
+                            Expr::value(Option::<chrono::DateTime<chrono::Local>>::None),
+                        )
+                        .exec(conn)
+                        .await?;
+                    invalidate_related_cache();
+                    reschedule();
+                }
+                // 发布时间不参与哈希计算，重新发布或调整定时发布时间时需要单独同步
+                if let Some(published_at) = markdown.published_at()
+                    && metadata.published_at != Some(published_at)
+                {
+                    info!("Updating publish time of {} to {}", &slug, published_at);
+                    content_metadata::Entity::update_many()
+                        .filter(content_metadata::Column::Id.eq(metadata.id))
+                        .col_expr(content_metadata::Column::PublishedAt, Expr::value(published_at))
+                        .exec(conn)
+                        .await?;
+                    invalidate_related_cache();
+                    reschedule();
+                }
+                if let Some(commit) = markdown.source_commit()
+                    && metadata.source_commit.as_deref() != Some(commit)
+                {
+                    content_metadata::Entity::update_many()
+                        .filter(content_metadata::Column::Id.eq(metadata.id))
+                        .col_expr(content_metadata::Column::SourceCommit, Expr::value(commit))
+                        .exec(conn)
+                        .await?;
+                }
+                let has_embedding = content_embedding::Entity::find_by_id(metadata.id)
+                    .one(conn)
+                    .await?
+                    .is_some();
+                if !has_embedding {
+                    info!("Embedding missing, generating: {}", &slug);
+                    let embedding = generate_article_embedding(&markdown).await?;
+                    save_embedding(metadata.id, embedding, conn).await?;
+                }
                 return Ok(());
             }
             let summary = generate_article_summary(&markdown).await?;
-            let (toc, rendered_html) = markdown.render_to_html()?;
+            let embedding = generate_article_embedding(&markdown).await?;
+            let (toc, rendered_html) = render_markdown(&markdown, true, conn).await?;
             let txn = conn.begin().await?;
-            match existing {
+            let metadata_id = match existing {
                 Some(metadata) => {
                     info!("Article already exists, updating: {}", &slug);
+                    let metadata_id = metadata.id;
                     update_article_internal(
                         markdown,
                         metadata,
@@ -384,42 +1146,335 @@ pub async fn handle_markdown_change(conn: &DatabaseConnection, change: MarkdownC
                         &txn,
                     )
                     .await?;
+                    metadata_id
                 }
                 None => {
                     info!("Article does not exist, creating: {}", &slug);
                     create_article_internal(markdown, cover_images, summary, toc, rendered_html, content_hash, &txn)
-                        .await?;
+                        .await?
                 }
-            }
+            };
+            save_embedding(metadata_id, embedding, &txn).await?;
+            save_aliases(metadata_id, &slug, &aliases, &txn).await?;
             txn.commit().await?;
+            invalidate_related_cache();
+            reschedule();
             info!("Article upserted: {}", &slug);
         }
-        MarkdownChange::Deleted(slug) => {
-            info!("Deleting article: {}", slug);
-            content_metadata::Entity::delete_many()
-                .filter(content_metadata::Column::Slug.eq(&slug))
+        MarkdownChange::Unpublished(markdown) => {
+            let slug = markdown.slug().to_owned();
+            save_media(markdown.media(), conn).await?;
+            save_preview(markdown, conn).await?;
+            // 只记录撤回时间并保留原发布时间，重新发布时无需 front matter 再次声明
+            let result = content_metadata::Entity::update_many()
+                .filter(
+                    content_metadata::Column::Slug
+                        .eq(&slug)
+                        .and(content_metadata::Column::UnpublishedAt.is_null()),
+                )
+                .col_expr(
+                    content_metadata::Column::UnpublishedAt,
+                    Expr::value(chrono::Local::now()),
+                )
                 .exec(conn)
                 .await?;
+            if result.rows_affected > 0 {
+                info!("Article unpublished: {}", slug);
+                invalidate_related_cache();
+                reschedule();
+            }
+        }
+        MarkdownChange::Deleted(slug) => {
+            info!("Moving article to trash: {}", slug);
+            move_to_trash(content_metadata::Column::Slug.eq(&slug), conn).await?;
+            content_preview::Entity::delete_by_id(&slug).exec(conn).await?;
+            invalidate_slug_cache(&slug);
+            invalidate_related_cache();
         }
         MarkdownChange::SyncExisting(existing_slugs) => {
             info!("Syncing existing articles, found {} files", existing_slugs.len());
-            content_metadata::Entity::delete_many()
-                .filter(content_metadata::Column::Slug.is_not_in(existing_slugs))
+            let trashed = move_to_trash(content_metadata::Column::Slug.is_not_in(existing_slugs.clone()), conn).await?;
+            if trashed > 0 {
+                info!("Moved {} missing articles to trash", trashed);
+            }
+            content_preview::Entity::delete_many()
+                .filter(content_preview::Column::Slug.is_not_in(existing_slugs))
                 .exec(conn)
                 .await?;
+            clear_slug_cache();
+            invalidate_related_cache();
         }
         MarkdownChange::Renamed(old_slug, new_slug) => {
             info!("Renaming article from {} to {}", old_slug, new_slug);
+            let txn = conn.begin().await?;
+            let metadata_id = content_metadata::Entity::find()
+                .select_only()
+                .column(content_metadata::Column::Id)
+                .filter(content_metadata::Column::Slug.eq(&old_slug))
+                .into_tuple::<i32>()
+                .one(&txn)
+                .await?;
+            // 回收站中占用新 slug 的内容与改名后的文章合并，保留其评论、点赞与浏览数据
+            let trashed = content_metadata::Entity::find()
+                .filter(
+                    content_metadata::Column::Slug
+                        .eq(&new_slug)
+                        .and(content_metadata::Column::DeletedAt.is_not_null()),
+                )
+                .one(&txn)
+                .await?;
+            if let (Some(metadata_id), Some(trashed)) = (metadata_id, trashed) {
+                info!("Merging trashed article {} into renamed article", &new_slug);
+                merge_trashed(trashed, metadata_id, &txn).await?;
+            }
             content_metadata::Entity::update_many()
                 .filter(content_metadata::Column::Slug.eq(&old_slug))
-                .col_expr(content_metadata::Column::Slug, Expr::value(new_slug))
+                .col_expr(content_metadata::Column::Slug, Expr::value(new_slug.clone()))
+                .exec(&txn)
+                .await?;
+            content_preview::Entity::update_many()
+                .filter(content_preview::Column::Slug.eq(&old_slug))
+                .col_expr(content_preview::Column::Slug, Expr::value(new_slug.clone()))
+                .exec(&txn)
+                .await?;
+            // 新 slug 不再作为跳转来源，旧 slug 指向改名后的文章
+            slug_redirect::Entity::delete_by_id(&new_slug).exec(&txn).await?;
+            if let Some(metadata_id) = metadata_id {
+                slug_redirect::Entity::insert(slug_redirect::ActiveModel {
+                    old_slug: Set(old_slug.clone()),
+                    content_metadata_id: Set(metadata_id),
+                    created_at: Set(chrono::Local::now()),
+                })
+                .on_conflict(
+                    OnConflict::column(slug_redirect::Column::OldSlug)
+                        .update_columns([
+                            slug_redirect::Column::ContentMetadataId,
+                            slug_redirect::Column::CreatedAt,
+                        ])
+                        .to_owned(),
+                )
+                .exec(&txn)
+                .await?;
+            }
+            txn.commit().await?;
+            invalidate_slug_cache(&old_slug);
+            invalidate_slug_cache(&new_slug);
+            invalidate_related_cache();
+        }
+    }
+    Ok(())
+}
+
+/// 将回收站中的内容合并到另一篇内容，评论、点赞、旧 slug 与浏览数转移后删除该内容
+async fn merge_trashed(trashed: content_metadata::Model, metadata_id: i32, conn: &impl ConnectionTrait) -> Result<()> {
+    comment::Entity::update_many()
+        .filter(comment::Column::ContentMetadataId.eq(trashed.id))
+        .col_expr(comment::Column::ContentMetadataId, Expr::value(metadata_id))
+        .exec(conn)
+        .await?;
+    // 同一身份只保留一个点赞，重复的点赞随内容一起删除
+    like::Entity::update_many()
+        .filter(
+            like::Column::ContentMetadataId.eq(trashed.id).and(
+                like::Column::IdentityId.not_in_subquery(
+                    Query::select()
+                        .column(like::Column::IdentityId)
+                        .from(like::Entity)
+                        .and_where(like::Column::ContentMetadataId.eq(metadata_id))
+                        .to_owned(),
+                ),
+            ),
+        )
+        .col_expr(like::Column::ContentMetadataId, Expr::value(metadata_id))
+        .exec(conn)
+        .await?;
+    slug_redirect::Entity::update_many()
+        .filter(slug_redirect::Column::ContentMetadataId.eq(trashed.id))
+        .col_expr(slug_redirect::Column::ContentMetadataId, Expr::value(metadata_id))
+        .exec(conn)
+        .await?;
+    content_metadata::Entity::delete_by_id(trashed.id).exec(conn).await?;
+    let comment_count = comment::Entity::find()
+        .filter(
+            comment::Column::ContentMetadataId
+                .eq(metadata_id)
+                .and(comment::Column::ParentId.is_null()),
+        )
+        .count(conn)
+        .await?;
+    let like_count = like::Entity::find()
+        .filter(like::Column::ContentMetadataId.eq(metadata_id))
+        .count(conn)
+        .await?;
+    content_metadata::Entity::update_many()
+        .filter(content_metadata::Column::Id.eq(metadata_id))
+        .col_expr(
+            content_metadata::Column::ViewCount,
+            Expr::col(content_metadata::Column::ViewCount).add(trashed.view_count),
+        )
+        .col_expr(content_metadata::Column::CommentCount, Expr::value(comment_count))
+        .col_expr(content_metadata::Column::LikeCount, Expr::value(like_count))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// 将 front matter 中声明的旧 slug 记录为跳转来源
+async fn save_aliases(metadata_id: i32, slug: &str, aliases: &[String], conn: &impl ConnectionTrait) -> Result<()> {
+    let aliases = aliases
+        .iter()
+        .filter(|alias| !alias.is_empty() && *alias != slug)
+        .unique()
+        .collect::<Vec<_>>();
+    if aliases.is_empty() {
+        return Ok(());
+    }
+    slug_redirect::Entity::insert_many(aliases.into_iter().map(|alias| slug_redirect::ActiveModel {
+        old_slug: Set(alias.clone()),
+        content_metadata_id: Set(metadata_id),
+        created_at: Set(chrono::Local::now()),
+    }))
+    .on_conflict(
+        OnConflict::column(slug_redirect::Column::OldSlug)
+            .update_column(slug_redirect::Column::ContentMetadataId)
+            .to_owned(),
+    )
+    .exec(conn)
+    .await?;
+    Ok(())
+}
+
+/// 渲染 markdown，已记录的图片会补充尺寸、占位图与响应式版本
+/// with_alt_texts 为 true 时为缺少替代文本的图片生成描述，只在发布时使用，草稿中的图片不会发送给模型
+async fn render_markdown(
+    markdown: &Markdown,
+    with_alt_texts: bool,
+    conn: &impl ConnectionTrait,
+) -> Result<(Option<suwen_entity::Toc>, Option<String>)> {
+    let urls = markdown.extract_resources()?.into_iter().map(|r| r.url().to_owned());
+    let media = get_media(urls, conn).await?;
+    let alt_texts = if with_alt_texts {
+        get_alt_texts(markdown.extract_images_without_alt()?, &media, conn).await?
+    } else {
+        HashMap::new()
+    };
+    markdown.render_to_html(&media, &alt_texts)
+}
+
+/// 为缺少替代文本的图片生成描述，按图片内容的哈希缓存，生成失败的图片保持原样
+async fn get_alt_texts(
+    urls: Vec<String>,
+    media: &HashMap<String, media::Model>,
+    conn: &impl ConnectionTrait,
+) -> Result<HashMap<String, String>> {
+    let mut alt_texts = HashMap::new();
+    if CONFIG.openai_vision_model.is_none() {
+        return Ok(alt_texts);
+    }
+    for url in urls {
+        let Some(media) = media.get(&url) else {
+            continue;
+        };
+        let Some(hash) = &media.hash else {
+            continue;
+        };
+        if let Some(cached) = image_alt_text::Entity::find_by_id(hash).one(conn).await? {
+            alt_texts.insert(url, cached.alt_text);
+            continue;
+        }
+        // 使用接近正文宽度的版本，减少模型需要下载的数据
+        let image_url = media
+            .variants
+            .0
+            .iter()
+            .filter(|v| v.mime_type == "image/webp" && v.width >= 512)
+            .min_by_key(|v| v.width)
+            .map_or(url.as_str(), |v| v.url.as_str());
+        match generate_alt_text(image_url).await {
+            Ok(Some((model, alt_text))) => {
+                info!("Generated alt text for {}: {}", url, alt_text);
+                image_alt_text::Entity::insert(image_alt_text::ActiveModel {
+                    hash: Set(hash.clone()),
+                    alt_text: Set(alt_text.clone()),
+                    model: Set(model),
+                    created_at: Set(chrono::Local::now()),
+                })
+                .on_conflict(OnConflict::column(image_alt_text::Column::Hash).do_nothing().to_owned())
+                .do_nothing()
                 .exec(conn)
                 .await?;
+                alt_texts.insert(url, alt_text);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to generate alt text for {}: {}", url, e),
         }
     }
+    Ok(alt_texts)
+}
+
+/// 渲染草稿用于预览，不生成摘要与 embedding
+async fn save_preview(mut markdown: Markdown, conn: &impl ConnectionTrait) -> Result<()> {
+    let cover_images = markdown.extract_images()?;
+    markdown.strip_images()?;
+    markdown.auto_format()?;
+    let (toc, rendered_html) = render_markdown(&markdown, false, conn).await?;
+    content_preview::Entity::insert(content_preview::ActiveModel {
+        slug: Set(markdown.slug().to_owned()),
+        content_type: Set(markdown.content_type().to_owned()),
+        title: Set(markdown.title().to_owned()),
+        rendered_html: Set(rendered_html),
+        toc: Set(toc),
+        tags: Set(markdown.tags().into()),
+        cover_images: Set(cover_images.into()),
+        published_at: Set(markdown.published_at()),
+        updated_at: Set(chrono::Local::now()),
+    })
+    .on_conflict(
+        OnConflict::column(content_preview::Column::Slug)
+            .update_columns([
+                content_preview::Column::ContentType,
+                content_preview::Column::Title,
+                content_preview::Column::RenderedHtml,
+                content_preview::Column::Toc,
+                content_preview::Column::Tags,
+                content_preview::Column::CoverImages,
+                content_preview::Column::PublishedAt,
+                content_preview::Column::UpdatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec(conn)
+    .await?;
+    info!("Draft preview saved: {}", markdown.slug());
     Ok(())
 }
 
+/// 获取草稿预览，返回与文章详情相同的结构
+pub async fn get_preview_by_slug(conn: &DatabaseConnection, slug: &str) -> Result<Option<ArticleBySlug>> {
+    let Some(preview) = content_preview::Entity::find_by_id(slug).one(conn).await? else {
+        return Ok(None);
+    };
+    Ok(Some(ArticleBySlug {
+        id: 0,
+        series_id: None,
+        title: preview.title,
+        rendered_html: preview.rendered_html.unwrap_or_default(),
+        summary: None,
+        intro: None,
+        tags: preview.tags,
+        toc: preview.toc.unwrap_or_else(|| Vec::new().into()),
+        view_count: 0,
+        comment_count: 0,
+        like_count: 0,
+        published_at: preview.published_at.unwrap_or(preview.updated_at),
+        series: None,
+    }))
+}
+
+pub async fn preview_exists(conn: &DatabaseConnection, slug: &str) -> Result<bool> {
+    Ok(content_preview::Entity::find_by_id(slug).one(conn).await?.is_some())
+}
+
 async fn create_article_internal(
     markdown: Markdown,
     cover_images: Vec<String>,
@@ -428,7 +1483,8 @@ async fn create_article_internal(
     rendered_html: Option<String>,
     content_hash: String,
     conn: &impl ConnectionTrait,
-) -> Result<()> {
+) -> Result<i32> {
+    let (series_id, series_order) = resolve_series(&markdown, conn).await?;
     let metadata = content_metadata::ActiveModel {
         slug: Set(markdown.slug().to_owned()),
         content_hash: Set(content_hash),
@@ -441,12 +1497,17 @@ async fn create_article_internal(
         updated_at: markdown.updated_at().map(Set).unwrap_or(NotSet),
         published_at: markdown.published_at().map(|dt| Set(Some(dt))).unwrap_or(NotSet),
         original_lang: Set(markdown.lang().to_string()),
+        series_id: Set(series_id),
+        series_order: Set(series_order),
+        source_commit: Set(markdown.source_commit().map(str::to_owned)),
         ..Default::default()
     };
     let metadata_id = content_metadata::Entity::insert(metadata)
         .exec(conn)
         .await?
         .last_insert_id;
+    // slug 被新文章占用后，不再跳转到改名前的文章
+    slug_redirect::Entity::delete_by_id(markdown.slug()).exec(conn).await?;
 
     let content = content::ActiveModel {
         title: Set(markdown.title().to_owned()),
@@ -473,6 +1534,51 @@ async fn create_article_internal(
             .exec(conn)
             .await?;
     }
+    Ok(metadata_id)
+}
+
+/// 返回文章所属系列的 id 与顺序，系列不存在时自动创建
+async fn resolve_series(markdown: &Markdown, conn: &impl ConnectionTrait) -> Result<(Option<i32>, Option<i32>)> {
+    let Some(series) = markdown.series() else {
+        return Ok((None, None));
+    };
+    series::Entity::insert(series::ActiveModel {
+        name: Set(series.name.clone()),
+        ..Default::default()
+    })
+    .on_conflict(OnConflict::column(series::Column::Name).do_nothing().to_owned())
+    .do_nothing()
+    .exec(conn)
+    .await?;
+    let series_id = series::Entity::find()
+        .select_only()
+        .column(series::Column::Id)
+        .filter(series::Column::Name.eq(&series.name))
+        .into_tuple::<i32>()
+        .one(conn)
+        .await?
+        .context("series not found")?;
+    Ok((Some(series_id), Some(series.order)))
+}
+
+async fn save_embedding(metadata_id: i32, embedding: Embedding, conn: &impl ConnectionTrait) -> Result<()> {
+    content_embedding::Entity::insert(content_embedding::ActiveModel {
+        content_metadata_id: Set(metadata_id),
+        model: Set(embedding.model),
+        vector: Set(Vector(embedding.vector)),
+        updated_at: Set(chrono::Local::now()),
+    })
+    .on_conflict(
+        OnConflict::column(content_embedding::Column::ContentMetadataId)
+            .update_columns([
+                content_embedding::Column::Model,
+                content_embedding::Column::Vector,
+                content_embedding::Column::UpdatedAt,
+            ])
+            .to_owned(),
+    )
+    .exec(conn)
+    .await?;
     Ok(())
 }
 
@@ -488,13 +1594,24 @@ async fn update_article_internal(
     conn: &impl ConnectionTrait,
 ) -> Result<()> {
     let metadata_id = metadata.id;
+    let (series_id, series_order) = resolve_series(&markdown, conn).await?;
     let metadata = content_metadata::ActiveModel {
         content_hash: Set(content_hash),
         content_type: Set(markdown.content_type().to_owned()),
         cover_images: Set(cover_images.into()),
         tags: Set(markdown.tags().into()),
-        updated_at: Set(chrono::Local::now()),
+        // 来自 git 的内容以最后一次提交的时间作为更新时间
+        updated_at: Set(markdown
+            .source_commit()
+            .and(markdown.updated_at())
+            .unwrap_or_else(chrono::Local::now)),
+        published_at: markdown.published_at().map(|dt| Set(Some(dt))).unwrap_or(NotSet),
         original_lang: Set(markdown.lang().to_string()),
+        series_id: Set(series_id),
+        series_order: Set(series_order),
+        deleted_at: Set(None),
+        source_commit: Set(markdown.source_commit().map(str::to_owned)),
+        unpublished_at: Set(None),
         ..metadata.into()
     };
     content_metadata::Entity::update(metadata).exec(conn).await?;
@@ -595,6 +1712,118 @@ pub async fn get_comments_by_slug(conn: &DatabaseConnection, slug: &str) -> Resu
     Ok(comments)
 }
 
+/// 导入其它平台的评论，所属文章需要已同步到数据库，已导入的评论按 source_id 跳过，可以重复执行
+pub async fn import_comments(conn: &DatabaseConnection, comments: Vec<ImportedComment>) -> Result<usize> {
+    let groups = comments.into_iter().into_group_map_by(|comment| comment.slug.clone());
+    let mut metadata_ids = HashMap::new();
+    for slugs in &groups.keys().chunks(500) {
+        metadata_ids.extend(
+            content_metadata::Entity::find()
+                .select_only()
+                .columns([content_metadata::Column::Slug, content_metadata::Column::Id])
+                .filter(
+                    content_metadata::Column::Slug
+                        .is_in(slugs)
+                        .and(content_metadata::Column::DeletedAt.is_null()),
+                )
+                .into_tuple::<(String, i32)>()
+                .all(conn)
+                .await?,
+        );
+    }
+    let (mut identities, mut imported) = (HashMap::new(), 0);
+    for (slug, mut comments) in groups {
+        let Some(&metadata_id) = metadata_ids.get(&slug) else {
+            warn!("Article {} not found, skipping {} comments", slug, comments.len());
+            continue;
+        };
+        // 顶层评论先插入，回复才能找到父评论
+        comments.sort_by_key(|comment| (comment.parent_source_id.is_some(), comment.created_at));
+        let txn = conn.begin().await?;
+        let mut comment_ids = comment::Entity::find()
+            .select_only()
+            .columns([comment::Column::SourceId, comment::Column::Id])
+            .filter(comment::Column::SourceId.is_in(comments.iter().map(|comment| comment.source_id.as_str())))
+            .into_tuple::<(String, i32)>()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        for comment in comments {
+            if comment_ids.contains_key(&comment.source_id) {
+                continue;
+            }
+            let identity_id = match identities.get(&comment.author) {
+                Some(&identity_id) => identity_id,
+                None => {
+                    let identity_id = imported_identity(comment.author.clone(), &txn).await?;
+                    identities.insert(comment.author, identity_id);
+                    identity_id
+                }
+            };
+            let parent_id = comment
+                .parent_source_id
+                .and_then(|parent| comment_ids.get(&parent).copied());
+            let comment_id = comment::Entity::insert(comment::ActiveModel {
+                identity_id: Set(identity_id),
+                content_metadata_id: Set(metadata_id),
+                parent_id: Set(parent_id),
+                content: Set(comment.content),
+                source_id: Set(Some(comment.source_id.clone())),
+                created_at: Set(comment.created_at),
+                updated_at: Set(comment.created_at),
+                ..Default::default()
+            })
+            .exec(&txn)
+            .await?
+            .last_insert_id;
+            comment_ids.insert(comment.source_id, comment_id);
+            imported += 1;
+        }
+        let comment_count = comment::Entity::find()
+            .filter(
+                comment::Column::ContentMetadataId
+                    .eq(metadata_id)
+                    .and(comment::Column::ParentId.is_null()),
+            )
+            .count(&txn)
+            .await?;
+        content_metadata::Entity::update_many()
+            .filter(content_metadata::Column::Id.eq(metadata_id))
+            .col_expr(content_metadata::Column::CommentCount, Expr::value(comment_count))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+    }
+    Ok(imported)
+}
+
+/// 导入的评论者没有账号，按昵称复用同一个身份，没有昵称的共用一个匿名身份
+async fn imported_identity(display_name: Option<String>, conn: &impl ConnectionTrait) -> Result<i32> {
+    let existing = identity::Entity::find()
+        .filter(
+            identity::Column::UserId
+                .is_null()
+                .and(identity::Column::Uuid.is_null())
+                .and(match &display_name {
+                    Some(display_name) => identity::Column::DisplayName.eq(display_name),
+                    None => identity::Column::DisplayName.is_null(),
+                }),
+        )
+        .one(conn)
+        .await?;
+    if let Some(identity) = existing {
+        return Ok(identity.id);
+    }
+    Ok(identity::Entity::insert(identity::ActiveModel {
+        display_name: Set(display_name),
+        ..Default::default()
+    })
+    .exec(conn)
+    .await?
+    .last_insert_id)
+}
+
 pub async fn get_sitemap_articles(conn: &DatabaseConnection, lang: Lang) -> Result<Vec<SitemapUrl>> {
     Ok(content_metadata::Entity::find()
         .select_only()
@@ -604,10 +1833,302 @@ pub async fn get_sitemap_articles(conn: &DatabaseConnection, lang: Lang) -> Resu
             content::Column::LangCode
                 .eq(lang.to_string())
                 .and(content_metadata::Column::ContentType.eq("article"))
-                .and(content_metadata::Column::PublishedAt.is_not_null()),
+                .and(is_published()),
         )
         .order_by_desc(content_metadata::Column::UpdatedAt)
         .into_model::<SitemapUrl>()
         .all(conn)
         .await?)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::db::test_connection;
+
+    async fn insert_tagged(conn: &DatabaseConnection, slug: &str, tags: &[&str], published_at: (i32, u32, u32)) {
+        let tags = tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        let (year, month, day) = published_at;
+        let metadata = content_metadata::Entity::insert(content_metadata::ActiveModel {
+            slug: Set(slug.to_owned()),
+            content_hash: Set(slug.to_owned()),
+            cover_images: Set(Vec::new().into()),
+            tags: Set(tags.clone().into()),
+            content_type: Set("article".to_owned()),
+            published_at: Set(Some(Local.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap())),
+            ..Default::default()
+        })
+        .exec(conn)
+        .await
+        .unwrap();
+        for tag in tags {
+            content_metadata_tag::Entity::insert(content_metadata_tag::ActiveModel {
+                content_metadata_id: Set(metadata.last_insert_id),
+                tag_name: Set(tag),
+            })
+            .exec(conn)
+            .await
+            .unwrap();
+        }
+    }
+
+    async fn filtered(conn: &DatabaseConnection, filter: ArticleFilter) -> Vec<String> {
+        content_metadata::Entity::find()
+            .filter(filter_condition(&filter))
+            .order_by_asc(content_metadata::Column::Id)
+            .all(conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|metadata| metadata.slug)
+            .collect()
+    }
+
+    fn short(slug: &str, content: &str, publish: bool, published_at: Option<chrono::DateTime<Local>>) -> Markdown {
+        Markdown::Short {
+            slug: slug.to_owned(),
+            title: slug.to_owned(),
+            cover_images: None,
+            content: String::new(),
+            publish: Some(publish),
+            created_at: None,
+            updated_at: None,
+            published_at,
+            lang: CONFIG.source_lang,
+            source_commit: None,
+            media: Vec::new(),
+        }
+        .with_content(content.to_owned(), CONFIG.source_lang)
+    }
+
+    async fn visible_metadata(conn: &DatabaseConnection, slug: &str) -> Option<content_metadata::Model> {
+        content_metadata::Entity::find()
+            .filter(content_metadata::Column::Slug.eq(slug).and(is_published()))
+            .one(conn)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unpublish_keeps_published_at() {
+        let conn = test_connection().await;
+        let slug = "unpublish-round-trip";
+        let published_at = Local.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap();
+        handle_markdown_change(
+            &conn,
+            MarkdownChange::Upsert(short(slug, "正文", true, Some(published_at))),
+        )
+        .await
+        .unwrap();
+        assert!(visible_metadata(&conn, slug).await.is_some());
+
+        // 撤回与重新发布时 front matter 均未声明发布时间
+        for content in ["正文", "修改后的正文"] {
+            handle_markdown_change(&conn, MarkdownChange::Unpublished(short(slug, content, false, None)))
+                .await
+                .unwrap();
+            assert!(visible_metadata(&conn, slug).await.is_none());
+            handle_markdown_change(&conn, MarkdownChange::Upsert(short(slug, content, true, None)))
+                .await
+                .unwrap();
+            let metadata = visible_metadata(&conn, slug).await.unwrap();
+            assert_eq!(metadata.published_at, Some(published_at));
+            assert!(metadata.unpublished_at.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_contents_published_between() {
+        let conn = test_connection().await;
+        let now = Local::now();
+        let hours = |hours: i64| Some(now + chrono::Duration::hours(hours));
+        for (slug, published_at) in [
+            ("due-before", hours(-2)),
+            ("due-inside", hours(-1)),
+            ("due-trashed", hours(-1)),
+            ("due-unpublished", hours(-1)),
+            ("due-future", hours(1)),
+        ] {
+            handle_markdown_change(&conn, MarkdownChange::Upsert(short(slug, slug, true, published_at)))
+                .await
+                .unwrap();
+        }
+        handle_markdown_change(&conn, MarkdownChange::Deleted("due-trashed".to_owned()))
+            .await
+            .unwrap();
+        handle_markdown_change(
+            &conn,
+            MarkdownChange::Unpublished(short("due-unpublished", "due-unpublished", false, None)),
+        )
+        .await
+        .unwrap();
+
+        let from = now - chrono::Duration::minutes(90);
+        let due = get_contents_published_between(&conn, CONFIG.source_lang, from, now)
+            .await
+            .unwrap();
+        assert_eq!(
+            due.iter().map(|content| content.slug.as_str()).collect::<Vec<_>>(),
+            ["due-inside"]
+        );
+        assert_eq!(due[0].content_type, "gallery");
+        assert_eq!(get_next_scheduled_at(&conn).await.unwrap(), hours(1));
+    }
+
+    #[tokio::test]
+    async fn test_publish_checked_at() {
+        let conn = test_connection().await;
+        init(&conn).await.unwrap();
+        assert_eq!(get_publish_checked_at(&conn).await.unwrap(), None);
+        let checked_at = Local.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap();
+        set_publish_checked_at(&conn, checked_at).await.unwrap();
+        assert_eq!(get_publish_checked_at(&conn).await.unwrap(), Some(checked_at));
+    }
+
+    #[tokio::test]
+    async fn test_view_count_requires_published() {
+        let conn = test_connection().await;
+        let slug = "trashed-view-count";
+        handle_markdown_change(
+            &conn,
+            MarkdownChange::Upsert(short(slug, "正文", true, Some(Local::now()))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(increase_article_view_count(&conn, slug).await.unwrap(), 1);
+        handle_markdown_change(&conn, MarkdownChange::Deleted(slug.to_owned()))
+            .await
+            .unwrap();
+        assert!(increase_article_view_count(&conn, slug).await.is_err());
+
+        // 定时发布与取消发布的内容同样不能增加浏览数
+        let scheduled = "scheduled-view-count";
+        let tomorrow = Local::now() + chrono::Duration::days(1);
+        handle_markdown_change(
+            &conn,
+            MarkdownChange::Upsert(short(scheduled, "定时", true, Some(tomorrow))),
+        )
+        .await
+        .unwrap();
+        assert!(increase_article_view_count(&conn, scheduled).await.is_err());
+        let unpublished = "unpublished-view-count";
+        handle_markdown_change(
+            &conn,
+            MarkdownChange::Upsert(short(unpublished, "取消发布", true, Some(Local::now()))),
+        )
+        .await
+        .unwrap();
+        handle_markdown_change(
+            &conn,
+            MarkdownChange::Unpublished(short(unpublished, "取消发布", false, None)),
+        )
+        .await
+        .unwrap();
+        assert!(increase_article_view_count(&conn, unpublished).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rename_onto_trashed_slug() {
+        let conn = test_connection().await;
+        let (old_slug, new_slug) = ("rename-merge-old", "rename-merge-new");
+        for (slug, content) in [(old_slug, "改名前"), (new_slug, "已删除")] {
+            handle_markdown_change(
+                &conn,
+                MarkdownChange::Upsert(short(slug, content, true, Some(Local::now()))),
+            )
+            .await
+            .unwrap();
+        }
+        let identity = |name: &str| identity::ActiveModel {
+            display_name: Set(Some(name.to_owned())),
+            ..Default::default()
+        };
+        let first = identity::Entity::insert(identity("first"))
+            .exec(&conn)
+            .await
+            .unwrap()
+            .last_insert_id;
+        let second = identity::Entity::insert(identity("second"))
+            .exec(&conn)
+            .await
+            .unwrap()
+            .last_insert_id;
+        let old_id = visible_metadata(&conn, old_slug).await.unwrap().id;
+        let trashed_id = visible_metadata(&conn, new_slug).await.unwrap().id;
+        // 两篇内容都被 first 点赞，合并后只保留一个
+        for (identity_id, content_metadata_id) in [(first, old_id), (first, trashed_id), (second, trashed_id)] {
+            like::Entity::insert(like::ActiveModel {
+                identity_id: Set(identity_id),
+                content_metadata_id: Set(content_metadata_id),
+                ..Default::default()
+            })
+            .exec(&conn)
+            .await
+            .unwrap();
+        }
+        comment::Entity::insert(comment::ActiveModel {
+            identity_id: Set(second),
+            content_metadata_id: Set(trashed_id),
+            content: Set("评论".to_owned()),
+            ..Default::default()
+        })
+        .exec(&conn)
+        .await
+        .unwrap();
+        increase_article_view_count(&conn, new_slug).await.unwrap();
+        increase_article_view_count(&conn, new_slug).await.unwrap();
+        increase_article_view_count(&conn, old_slug).await.unwrap();
+        handle_markdown_change(&conn, MarkdownChange::Deleted(new_slug.to_owned()))
+            .await
+            .unwrap();
+
+        handle_markdown_change(&conn, MarkdownChange::Renamed(old_slug.to_owned(), new_slug.to_owned()))
+            .await
+            .unwrap();
+        let merged = visible_metadata(&conn, new_slug).await.unwrap();
+        assert_eq!(merged.id, old_id);
+        assert_eq!((merged.view_count, merged.comment_count, merged.like_count), (3, 1, 2));
+        assert!(
+            content_metadata::Entity::find_by_id(trashed_id)
+                .one(&conn)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(get_comments_by_slug(&conn, new_slug).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_filter_condition() {
+        let conn = test_connection().await;
+        insert_tagged(&conn, "rust-web", &["rust", "web"], (2025, 1, 10)).await;
+        insert_tagged(&conn, "rust", &["rust"], (2025, 2, 10)).await;
+        insert_tagged(&conn, "web", &["web", "draft"], (2025, 3, 10)).await;
+
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        let any = ArticleFilter {
+            tags: tags(&["rust", "web"]),
+            ..Default::default()
+        };
+        assert_eq!(filtered(&conn, any).await, ["rust-web", "rust", "web"]);
+        let all = ArticleFilter {
+            tags: tags(&["rust", "web", "rust"]),
+            match_all_tags: true,
+            ..Default::default()
+        };
+        assert_eq!(filtered(&conn, all).await, ["rust-web"]);
+        let exclude = ArticleFilter {
+            exclude_tags: tags(&["draft"]),
+            ..Default::default()
+        };
+        assert_eq!(filtered(&conn, exclude).await, ["rust-web", "rust"]);
+        let range = ArticleFilter {
+            from: Some(Local.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap()),
+            to: Some(Local.with_ymd_and_hms(2025, 2, 28, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(filtered(&conn, range).await, ["rust"]);
+    }
+}
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Local};
 use sea_orm::FromQueryResult;
 use serde::{Deserialize, Serialize};
-use suwen_entity::{RelatedLinks, Tabs, Toc, VecString};
+use suwen_entity::{MediaVariant, RelatedLinks, Tabs, Toc, VecString, media};
 
 use crate::routes::IdentityInfo;
 
@@ -21,20 +21,83 @@ pub struct Site {
 #[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
 #[serde(rename_all = "camelCase")]
 pub struct ArticleByList {
+    #[serde(skip)]
+    pub id: i32,
     pub slug: String,
     pub title: String,
     pub intro: Option<String>,
     pub summary: Option<String>,
+    #[serde(skip)]
     pub cover_images: VecString,
+    /// 封面图片，查询后根据 cover_images 填充
+    #[sea_orm(skip)]
+    #[serde(rename = "coverImages")]
+    pub covers: Vec<CoverImage>,
     pub tags: VecString,
     pub view_count: i32,
     pub comment_count: i32,
     pub published_at: DateTime<Local>,
 }
 
+/// 封面图片，附带已记录的尺寸、占位图与不同宽度的版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverImage {
+    pub url: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub placeholder: Option<String>,
+    pub variants: Vec<MediaVariant>,
+}
+
+impl CoverImage {
+    pub fn new(url: String, media: Option<&media::Model>) -> Self {
+        match media {
+            Some(media) => Self {
+                url,
+                width: media.width,
+                height: media.height,
+                placeholder: media.placeholder.clone(),
+                variants: media.variants.0.clone(),
+            },
+            None => Self {
+                url,
+                width: None,
+                height: None,
+                placeholder: None,
+                variants: Vec::new(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    #[default]
+    Keyword,
+    Semantic,
+}
+
+/// 文章列表的筛选条件
+#[derive(Debug, Clone, Default)]
+pub struct ArticleFilter {
+    pub tags: Vec<String>,
+    /// 为 true 时要求包含全部 tags，否则包含任意一个即可
+    pub match_all_tags: bool,
+    pub exclude_tags: Vec<String>,
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+    pub content_type: Option<&'static str>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
 #[serde(rename_all = "camelCase")]
 pub struct ArticleBySlug {
+    #[serde(skip)]
+    pub id: i32,
+    #[serde(skip)]
+    pub series_id: Option<i32>,
     pub title: String,
     pub rendered_html: String,
     pub summary: Option<String>,
@@ -45,6 +108,43 @@ pub struct ArticleBySlug {
     pub comment_count: i32,
     pub like_count: i32,
     pub published_at: DateTime<Local>,
+    #[sea_orm(skip)]
+    pub series: Option<SeriesNavigation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesWithCount {
+    pub name: String,
+    pub article_count: i32,
+    pub updated_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesDetail {
+    pub name: String,
+    pub articles: Vec<ArticleByList>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesLink {
+    #[serde(skip)]
+    pub id: i32,
+    pub slug: String,
+    pub title: String,
+}
+
+/// 文章在系列中的位置，position 从 1 开始
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesNavigation {
+    pub name: String,
+    pub position: usize,
+    pub total: usize,
+    pub prev: Option<SeriesLink>,
+    pub next: Option<SeriesLink>,
 }
 
 #[derive(Debug, Clone, FromQueryResult)]
@@ -62,11 +162,30 @@ pub struct ArticleForRSS {
 #[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
 #[serde(rename_all = "camelCase")]
 pub struct Short {
+    #[serde(skip)]
+    pub id: i32,
     pub slug: String,
     pub title: String,
+    #[serde(skip)]
     pub cover_images: VecString,
+    #[sea_orm(skip)]
+    #[serde(rename = "coverImages")]
+    pub covers: Vec<CoverImage>,
     pub content: String,
     pub rendered_html: Option<String>,
+    #[serde(skip)]
+    pub view_count: i32,
+    #[serde(skip)]
+    pub comment_count: i32,
+    pub published_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
@@ -76,6 +195,30 @@ pub struct TagWithCount {
     pub count: i32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledContent {
+    pub slug: String,
+    pub title: String,
+    pub content_type: String,
+    pub published_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedContent {
+    pub slug: String,
+    pub title: String,
+    pub content_type: String,
+    pub view_count: i32,
+    pub comment_count: i32,
+    pub like_count: i32,
+    pub deleted_at: DateTime<Local>,
+    /// 超过该时间后内容会被彻底删除
+    #[sea_orm(skip)]
+    pub purge_at: DateTime<Local>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromQueryResult)]
 #[serde(rename_all = "camelCase")]
 pub struct Archive {
@@ -11,16 +11,20 @@ use axum_reverse_proxy::ReverseProxy;
 use reqwest::{StatusCode, header};
 use sea_orm::DatabaseConnection;
 use suwen_config::CONFIG;
-use suwen_markdown::UPLOAD_DIR;
+use suwen_markdown::{UPLOAD_DIR, safe_relative_path};
 use tower::ServiceExt;
 use tower_http::services::ServeFile;
 
+pub use crate::media_gc::collect_orphaned_media;
 use crate::routes::UrlQuery;
+pub use crate::scheduler::{run_media_gc, run_publish_scheduler, run_trash_purger};
 
 mod auth;
 pub mod db;
+mod media_gc;
 mod routes;
 mod rss;
+mod scheduler;
 mod sitemap;
 mod wrapper;
 
@@ -30,14 +34,20 @@ static FRONTEND_ORIGIN: LazyLock<String> =
 pub fn router() -> Router {
     Router::new()
         .nest("/api", routes::router())
-        .route("/uploads/{file}", get(uploads_handler))
+        .route("/uploads/{*file}", get(uploads_handler))
         .route("/feed", get(rss_handler))
         .route("/sitemap.xml", get(sitemap_handler))
         .merge(ReverseProxy::new("/", FRONTEND_ORIGIN.as_str()))
 }
 
 async fn uploads_handler(AxumPath(path): AxumPath<String>, request: Request) -> impl IntoResponse {
-    ServeFile::new(UPLOAD_DIR.join(path)).oneshot(request).await
+    let Ok(path) = safe_relative_path(&path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    ServeFile::new(UPLOAD_DIR.join(path))
+        .oneshot(request)
+        .await
+        .into_response()
 }
 
 async fn rss_handler(
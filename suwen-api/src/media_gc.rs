@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, ensure};
+use itertools::Itertools;
+use regex::Regex;
+use sea_orm::DatabaseConnection;
+use suwen_config::CONFIG;
+use suwen_markdown::MarkdownProcessor;
+
+use crate::db;
+
+/// 匹配文本中形如 /{hash}.webp、/{hash}-960w.avif 的文件名
+static MEDIA_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"/(\d+)(?:-\d+w)?\.[A-Za-z0-9]+").unwrap());
+
+/// 删除存储中不再被任何内容引用的媒体文件，返回清理的文件路径，dry_run 时只列出不删除
+///
+/// 只按内容哈希判断引用，不区分 slug 与域名，同一图片被多篇文章引用时任一引用都会保留全部副本
+///
+/// 未配置前缀的 S3 存储中可能存放着其它程序的文件，只允许 dry_run
+pub async fn collect_orphaned_media(conn: &DatabaseConnection, dry_run: bool) -> Result<Vec<String>> {
+    let processor = MarkdownProcessor::get().await;
+    let store = processor.store();
+    ensure!(
+        dry_run || store.is_dedicated(),
+        "Media store has no prefix configured, refusing to delete objects that may not belong to suwen"
+    );
+    // 先列出文件再读取引用，读取期间新上传的文件不在列表中
+    let objects = store.list().await?;
+    let referenced = db::get_media_references(conn)
+        .await?
+        .iter()
+        .flat_map(|text| MEDIA_NAME.captures_iter(text).map(|c| c[1].to_owned()))
+        .collect::<HashSet<_>>();
+    let deadline = SystemTime::now() - Duration::from_secs(CONFIG.media_gc.grace_hours * 60 * 60);
+    let orphaned = objects
+        .into_iter()
+        .filter(|object| object.modified.is_none_or(|modified| modified < deadline))
+        .filter(|object| media_hash(&object.key).is_some_and(|hash| !referenced.contains(hash)))
+        .map(|object| object.key)
+        .sorted()
+        .collect_vec();
+    if dry_run || orphaned.is_empty() {
+        return Ok(orphaned);
+    }
+    let mut deleted = Vec::new();
+    for key in &orphaned {
+        match store.delete(key).await {
+            Ok(()) => deleted.push(store.public_url(key)),
+            Err(e) => warn!("Failed to delete orphaned media {}: {}", key, e),
+        }
+    }
+    db::delete_media(&deleted, conn).await?;
+    processor.forget_media(&deleted.into_iter().collect()).await?;
+    Ok(orphaned)
+}
+
+/// 解析上传流程生成的 {slug}/{hash}.{ext} 与 {slug}/{hash}-{width}w.{ext} 中的哈希，其它文件不会被清理
+fn media_hash(key: &str) -> Option<&str> {
+    let (slug, name) = key.split_once('/')?;
+    if slug.is_empty() || name.contains('/') {
+        return None;
+    }
+    let (stem, _) = name.rsplit_once('.')?;
+    let hash = match stem.rsplit_once('-') {
+        Some((hash, width)) if width.strip_suffix('w').is_some_and(is_digits) => hash,
+        _ => stem,
+    };
+    is_digits(hash).then_some(hash)
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_hash() {
+        assert_eq!(media_hash("hello/123.webp"), Some("123"));
+        assert_eq!(media_hash("hello/123-960w.avif"), Some("123"));
+        assert_eq!(media_hash("avatar.webp"), None);
+        assert_eq!(media_hash("icon/logo.png"), None);
+        assert_eq!(media_hash("a/b/123.webp"), None);
+        assert_eq!(media_hash("hello/123-w.webp"), None);
+        let html = r#"<img src="https://obj.example.com/p/hello/123-480w.webp 480w, /uploads/x/456.png">"#;
+        let hashes = MEDIA_NAME.captures_iter(html).map(|c| c[1].to_owned()).collect_vec();
+        assert_eq!(hashes, ["123", "456"]);
+    }
+}
@@ -0,0 +1,279 @@
+use std::path::{Path as FsPath, PathBuf};
+
+use axum::extract::{Path, Query};
+use axum::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use suwen_config::CONFIG;
+use suwen_markdown::{Markdown, find_markdown_file};
+use tokio::sync::Mutex;
+
+use crate::auth::Identity;
+use crate::routes::require_admin;
+use crate::wrapper::{ApiError, ApiResponse};
+
+// 串行化管理端的文件写入，避免并发请求在校验与写入之间互相覆盖
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MarkdownRequest {
+    #[serde(flatten)]
+    markdown: Markdown,
+    content: String,
+    /// 修改时读取到的内容哈希，与当前文件不一致时说明文件已被他人修改
+    expected_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct DeleteMarkdownQuery {
+    expected_hash: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MarkdownSource {
+    #[serde(flatten)]
+    markdown: Markdown,
+    content: String,
+    path: String,
+    hash: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SavedMarkdown {
+    slug: String,
+    path: String,
+    hash: String,
+}
+
+pub(super) async fn get_markdown(
+    Extension(identity): Extension<Identity>,
+    Path((slug,)): Path<(String,)>,
+) -> Result<ApiResponse<MarkdownSource>, ApiError> {
+    require_admin(&identity)?;
+    let root = markdown_root()?;
+    let (path, markdown) = find_markdown_file(&root, &slug)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Markdown file not found"))?;
+    Ok(ApiResponse::ok(MarkdownSource {
+        content: markdown.content().to_owned(),
+        path: relative_path(&root, &path),
+        hash: markdown.hash(),
+        markdown,
+    }))
+}
+
+pub(super) async fn create_markdown(
+    Extension(identity): Extension<Identity>,
+    Json(request): Json<MarkdownRequest>,
+) -> Result<ApiResponse<SavedMarkdown>, ApiError> {
+    require_admin(&identity)?;
+    create_file(&markdown_root()?, request).await
+}
+
+pub(super) async fn update_markdown(
+    Extension(identity): Extension<Identity>,
+    Path((slug,)): Path<(String,)>,
+    Json(request): Json<MarkdownRequest>,
+) -> Result<ApiResponse<SavedMarkdown>, ApiError> {
+    require_admin(&identity)?;
+    update_file(&markdown_root()?, &slug, request).await
+}
+
+pub(super) async fn delete_markdown(
+    Extension(identity): Extension<Identity>,
+    Path((slug,)): Path<(String,)>,
+    Query(query): Query<DeleteMarkdownQuery>,
+) -> Result<ApiResponse<()>, ApiError> {
+    require_admin(&identity)?;
+    delete_file(&markdown_root()?, &slug, &query.expected_hash).await
+}
+
+async fn create_file(root: &FsPath, request: MarkdownRequest) -> Result<ApiResponse<SavedMarkdown>, ApiError> {
+    let markdown = request.markdown.with_content(request.content, CONFIG.source_lang);
+    validate_slug(markdown.slug())?;
+    let _guard = WRITE_LOCK.lock().await;
+    let path = root.join(format!("{}.md", markdown.slug()));
+    if tokio::fs::try_exists(&path).await? || find_markdown_file(root, markdown.slug()).await?.is_some() {
+        return Err(ApiError::conflict("Slug already exists"));
+    }
+    save_markdown(root, path, markdown).await
+}
+
+async fn update_file(
+    root: &FsPath,
+    slug: &str,
+    request: MarkdownRequest,
+) -> Result<ApiResponse<SavedMarkdown>, ApiError> {
+    let expected_hash = request
+        .expected_hash
+        .ok_or_else(|| ApiError::bad_request("expectedHash is required"))?;
+    let markdown = request.markdown.with_content(request.content, CONFIG.source_lang);
+    validate_slug(markdown.slug())?;
+    let _guard = WRITE_LOCK.lock().await;
+    let (path, current) = find_markdown_file(root, slug)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Markdown file not found"))?;
+    if current.hash() != expected_hash {
+        return Err(ApiError::conflict("Markdown file has been modified"));
+    }
+    // 修改 slug 时由文件监听识别为改名，新 slug 不能被其它文件占用
+    if markdown.slug() != slug
+        && let Some((other, _)) = find_markdown_file(root, markdown.slug()).await?
+        && other != path
+    {
+        return Err(ApiError::conflict("Slug already exists"));
+    }
+    save_markdown(root, path, markdown).await
+}
+
+async fn delete_file(root: &FsPath, slug: &str, expected_hash: &str) -> Result<ApiResponse<()>, ApiError> {
+    let _guard = WRITE_LOCK.lock().await;
+    let (path, current) = find_markdown_file(root, slug)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Markdown file not found"))?;
+    if current.hash() != expected_hash {
+        return Err(ApiError::conflict("Markdown file has been modified"));
+    }
+    info!("Deleting markdown file {:?}", path);
+    tokio::fs::remove_file(&path).await?;
+    Ok(ApiResponse::ok(()))
+}
+
+/// 写入 markdown 文件，数据库由文件监听负责同步
+async fn save_markdown(
+    root: &FsPath,
+    path: PathBuf,
+    markdown: Markdown,
+) -> Result<ApiResponse<SavedMarkdown>, ApiError> {
+    info!("Writing markdown file {:?}", path);
+    tokio::fs::write(&path, markdown.to_string()?).await?;
+    Ok(ApiResponse::ok(SavedMarkdown {
+        slug: markdown.slug().to_owned(),
+        path: relative_path(root, &path),
+        hash: markdown.hash(),
+    }))
+}
+
+/// 只有监听本地目录时写入的文件才会被同步，git 仓库的工作副本会在下次拉取时被重置
+fn markdown_root() -> Result<PathBuf, ApiError> {
+    if CONFIG.git_source.is_some() {
+        return Err(ApiError::bad_request("Content is synced from a git repository"));
+    }
+    CONFIG
+        .markdown_path
+        .as_ref()
+        .map(PathBuf::from)
+        .ok_or_else(|| ApiError::bad_request("Markdown path is not configured"))
+}
+
+pub(super) fn validate_slug(slug: &str) -> Result<(), ApiError> {
+    if slug.is_empty() || slug.starts_with('.') || slug.contains(['/', '\\']) {
+        return Err(ApiError::bad_request("Invalid slug"));
+    }
+    Ok(())
+}
+
+fn relative_path(root: &FsPath, path: &FsPath) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    use super::*;
+
+    fn request(slug: &str, content: &str, expected_hash: Option<&str>) -> MarkdownRequest {
+        serde_json::from_value(serde_json::json!({
+            "type": "article",
+            "slug": slug,
+            "title": "标题",
+            "tags": ["rust"],
+            "content": content,
+            "expectedHash": expected_hash,
+        }))
+        .unwrap()
+    }
+
+    fn status<T: Serialize>(result: Result<ApiResponse<T>, ApiError>) -> StatusCode {
+        match result {
+            Ok(resp) => resp.into_response().status(),
+            Err(e) => e.into_response().status(),
+        }
+    }
+
+    #[test]
+    fn test_validate_slug() {
+        assert!(validate_slug("hello-world").is_ok());
+        assert!(validate_slug("你好").is_ok());
+        for slug in ["", ".hidden", "a/b", "a\\b", "../escape"] {
+            assert!(validate_slug(slug).is_err(), "{slug}");
+        }
+    }
+
+    #[test]
+    fn test_markdown_json_round_trip() {
+        let markdown = request("round-trip", "正文", None)
+            .markdown
+            .with_content("正文".to_owned(), CONFIG.source_lang);
+        let source = serde_json::to_value(MarkdownSource {
+            content: markdown.content().to_owned(),
+            path: "round-trip.md".to_owned(),
+            hash: markdown.hash(),
+            markdown,
+        })
+        .unwrap();
+        // front matter 字段与正文平铺在同一层级，类型由 type 字段区分
+        assert_eq!(source["type"], "article");
+        assert_eq!(source["slug"], "round-trip");
+        assert_eq!(source["tags"], serde_json::json!(["rust"]));
+        assert_eq!(source["content"], "正文");
+
+        let request: MarkdownRequest = serde_json::from_value(source.clone()).unwrap();
+        let markdown = request.markdown.with_content(request.content, CONFIG.source_lang);
+        assert_eq!(markdown.hash(), source["hash"]);
+        assert!(request.expected_hash.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_modification_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(
+            status(create_file(root, request("conflict", "第一版", None)).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(create_file(root, request("conflict", "第一版", None)).await),
+            StatusCode::CONFLICT
+        );
+        let (_, current) = find_markdown_file(root, "conflict").await.unwrap().unwrap();
+        let hash = current.hash();
+
+        assert_eq!(
+            status(update_file(root, "conflict", request("conflict", "第二版", None)).await),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(update_file(root, "conflict", request("conflict", "第二版", Some(&hash))).await),
+            StatusCode::OK
+        );
+        // 基于旧版本的修改与删除均被拒绝
+        assert_eq!(
+            status(update_file(root, "conflict", request("conflict", "第三版", Some(&hash))).await),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(status(delete_file(root, "conflict", &hash).await), StatusCode::CONFLICT);
+        let (path, current) = find_markdown_file(root, "conflict").await.unwrap().unwrap();
+        assert!(current.content().contains("第二版"));
+        assert_eq!(
+            status(delete_file(root, "conflict", &current.hash()).await),
+            StatusCode::OK
+        );
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::Path as FsPath;
+
+use axum::Extension;
+use axum::extract::{Multipart, Query};
+use serde::Deserialize;
+use suwen_markdown::{MarkdownProcessor, StoredMedia};
+
+use crate::auth::Identity;
+use crate::db::{self, DatabaseConnection};
+use crate::routes::authoring::validate_slug;
+use crate::routes::require_admin;
+use crate::wrapper::{ApiError, ApiResponse};
+
+/// 单个媒体文件的体积上限
+pub(super) const MAX_UPLOAD_SIZE: usize = 64 * 1024 * 1024;
+
+const ALLOWED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "avif", "mp4", "webm", "mov"];
+
+#[derive(Deserialize)]
+pub(super) struct UploadQuery {
+    /// 媒体所属文章的 slug，决定上传后的存储路径
+    slug: Option<String>,
+}
+
+/// 上传编辑器中粘贴或选择的文件，处理方式与 markdown 中引用的本地媒体一致
+pub(super) async fn upload_media(
+    Extension(identity): Extension<Identity>,
+    Extension(conn): Extension<DatabaseConnection>,
+    Query(query): Query<UploadQuery>,
+    mut multipart: Multipart,
+) -> Result<ApiResponse<StoredMedia>, ApiError> {
+    require_admin(&identity)?;
+    let slug = query.slug.unwrap_or_else(|| "uploads".to_owned());
+    validate_slug(&slug)?;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::bad_request(e.body_text()))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let ext = field
+            .file_name()
+            .and_then(|name| FsPath::new(name).extension())
+            .and_then(|ext| ext.to_str())
+            .or_else(|| {
+                field
+                    .content_type()
+                    .and_then(mime_guess::get_mime_extensions_str)
+                    .and_then(|exts| exts.first())
+                    .copied()
+            })
+            .map(str::to_lowercase)
+            .filter(|ext| ALLOWED_EXTENSIONS.contains(&ext.as_str()))
+            .ok_or_else(|| ApiError::bad_request("Unsupported media type"))?;
+        let data = field.bytes().await.map_err(|e| ApiError::bad_request(e.body_text()))?;
+        if data.is_empty() {
+            return Err(ApiError::bad_request("Empty file"));
+        }
+        let stored = MarkdownProcessor::get()
+            .await
+            .upload_data(&slug, data.to_vec(), &ext)
+            .await?;
+        db::save_media(std::slice::from_ref(&stored), &conn).await?;
+        info!("Uploaded media {}", stored.url);
+        return Ok(ApiResponse::ok(stored));
+    }
+    Err(ApiError::bad_request("Missing file field"))
+}
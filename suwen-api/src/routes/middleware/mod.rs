@@ -7,7 +7,7 @@ use axum_extra::extract::CookieJar;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use uuid::Uuid;
 
-use crate::auth::{Claims, Identity};
+use crate::auth::{Claims, Identity, Token};
 use crate::wrapper::ApiError;
 
 pub(crate) async fn auth(
@@ -1,21 +1,25 @@
 use anyhow::Context;
 use axum::Extension;
-use axum::extract::{Path, Query};
-use axum::http::HeaderValue;
-use axum::response::IntoResponse;
+use axum::extract::{DefaultBodyLimit, Path, Query, RawQuery};
+use axum::http::{HeaderValue, header};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum_extra::extract::cookie::{Cookie, SameSite};
+use chrono::{DateTime, Local, NaiveDate};
 pub(crate) use schema::IdentityInfo;
 use sea_orm::ActiveValue::Set as ActiveSet;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, Set, TransactionTrait};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use suwen_config::CONFIG;
 use suwen_entity::content_metadata;
 use suwen_migration::Expr;
 
-use crate::auth::Identity;
+use crate::auth::{Identity, PreviewClaims, Token};
 use crate::db::{self, Archive, Comment, get_metadata_id_for_slug};
 use crate::wrapper::{ApiError, ApiResponse};
 
+mod authoring;
+mod media;
 mod middleware;
 mod schema;
 
@@ -35,11 +39,70 @@ struct DeleteCommentRequest {
     id: i32,
 }
 
+#[derive(Serialize)]
+struct SlugRedirect {
+    slug: String,
+}
+
+#[derive(Deserialize)]
+struct PreviewQuery {
+    /// 预览链接的有效期，单位为小时
+    ttl: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewLink {
+    token: String,
+    url: String,
+    expires_at: DateTime<Local>,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum RelatedBy {
+    #[default]
+    Embedding,
+    Tags,
+}
+
+#[derive(Deserialize)]
+struct RelatedQuery {
+    #[serde(default)]
+    by: RelatedBy,
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    #[serde(default)]
+    mode: db::SearchMode,
+}
+
+/// 单次请求最多返回的条数
+const MAX_LIMIT: u64 = 100;
+
 pub(super) struct UrlQuery {
     pub lang: Option<db::Lang>,
     pub sort: Option<content_metadata::Column>,
     pub published: Option<bool>,
     pub limit: Option<u64>,
+    pub cursor: Option<String>,
+    pub filter: db::ArticleFilter,
+}
+
+impl UrlQuery {
+    fn sort_column(&self) -> content_metadata::Column {
+        self.sort.unwrap_or(content_metadata::Column::PublishedAt)
+    }
+
+    fn cursor(&self) -> Result<Option<db::Cursor>, ApiError> {
+        self.cursor
+            .as_deref()
+            .map(|cursor| db::Cursor::decode(cursor, self.sort_column()))
+            .transpose()
+            .map_err(|e| ApiError::bad_request(e.to_string()))
+    }
 }
 
 impl<'de> serde::Deserialize<'de> for UrlQuery {
@@ -51,6 +114,8 @@ impl<'de> serde::Deserialize<'de> for UrlQuery {
         let mut sort = None;
         let mut published = None;
         let mut limit = None;
+        let mut cursor = None;
+        let mut filter = db::ArticleFilter::default();
 
         let map: serde_json::Map<String, serde_json::Value> = serde_json::Map::deserialize(deserializer)?;
 
@@ -64,21 +129,94 @@ impl<'de> serde::Deserialize<'de> for UrlQuery {
                 _ => {}
             }
         }
+        // 查询参数均以字符串形式传入，需要手动解析
         if let Some(value) = map.get("published") {
-            published = value.as_bool();
+            published = value.as_bool().or_else(|| value.as_str().and_then(|s| s.parse().ok()));
         }
         if let Some(value) = map.get("limit") {
-            limit = value.as_u64();
+            limit = value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+                .map(|limit: u64| limit.min(MAX_LIMIT));
+        }
+        if let Some(value) = map.get("cursor") {
+            cursor = value.as_str().filter(|s| !s.is_empty()).map(String::from);
+        }
+        if let Some(value) = map.get("tags") {
+            filter.tags = split_list(value);
+        }
+        if let Some(value) = map.get("tag_match") {
+            filter.match_all_tags = value.as_str() == Some("all");
+        }
+        if let Some(value) = map.get("exclude_tags") {
+            filter.exclude_tags = split_list(value);
+        }
+        // 日期格式错误时直接拒绝，避免静默返回未过滤的列表
+        if let Some(value) = map.get("from") {
+            filter.from = date_param::<D::Error>("from", value, false)?;
+        }
+        if let Some(value) = map.get("to") {
+            filter.to = date_param::<D::Error>("to", value, true)?;
+        }
+        if let Some(value) = map.get("type") {
+            match value.as_str() {
+                Some("article") => filter.content_type = Some("article"),
+                Some("short") | Some("gallery") => filter.content_type = Some("gallery"),
+                _ => {}
+            }
         }
         Ok(Self {
             lang,
             sort,
             published,
             limit,
+            cursor,
+            filter,
         })
     }
 }
 
+fn split_list(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_str()
+        .map(|s| {
+            s.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn date_param<E: serde::de::Error>(
+    name: &str,
+    value: &serde_json::Value,
+    end_of_day: bool,
+) -> Result<Option<DateTime<Local>>, E> {
+    match value.as_str() {
+        Some("") => Ok(None),
+        Some(s) => parse_date(s, end_of_day)
+            .map(Some)
+            .ok_or_else(|| E::custom(format!("invalid `{name}` date: {s}"))),
+        None => Err(E::custom(format!("invalid `{name}` date"))),
+    }
+}
+
+/// 支持 RFC 3339 时间与 `YYYY-MM-DD` 日期，日期作为结束时间时包含当天
+fn parse_date(value: &str, end_of_day: bool) -> Option<DateTime<Local>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Local));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let time = if end_of_day {
+        date.and_hms_nano_opt(23, 59, 59, 999_999_999)?
+    } else {
+        date.and_hms_opt(0, 0, 0)?
+    };
+    time.and_local_timezone(Local).earliest()
+}
+
 async fn me(Extension(identity): Extension<Identity>) -> impl IntoResponse {
     if !matches!(identity, Identity::None) {
         ApiResponse::ok(Into::<IdentityInfo>::into(identity)).into_response()
@@ -105,14 +243,21 @@ async fn get_site(Extension(conn): Extension<DatabaseConnection>) -> Result<ApiR
 
 async fn get_articles(
     Extension(conn): Extension<DatabaseConnection>,
+    Extension(identity): Extension<Identity>,
     Query(query): Query<UrlQuery>,
-) -> Result<ApiResponse<Vec<db::ArticleByList>>, ApiError> {
+) -> Result<ApiResponse<db::Page<db::ArticleByList>>, ApiError> {
+    // 未发布的列表包括定时发布与取消发布的内容，只对管理员可见
+    if query.published == Some(false) {
+        require_admin(&identity)?;
+    }
     Ok(ApiResponse::ok(
         db::get_articles(
             &conn,
             query.lang.unwrap_or(db::Lang::ZhCN),
-            query.sort.unwrap_or(content_metadata::Column::PublishedAt),
+            query.sort_column(),
             query.published,
+            &query.filter,
+            query.cursor()?.as_ref(),
             query.limit.unwrap_or(100),
         )
         .await?,
@@ -121,15 +266,21 @@ async fn get_articles(
 
 async fn get_shorts(
     Extension(conn): Extension<DatabaseConnection>,
+    Extension(identity): Extension<Identity>,
     Query(query): Query<UrlQuery>,
-) -> Result<ApiResponse<Vec<db::Short>>, ApiError> {
+) -> Result<ApiResponse<db::Page<db::Short>>, ApiError> {
+    // 未发布的列表包括定时发布与取消发布的内容，只对管理员可见
+    if query.published == Some(false) {
+        require_admin(&identity)?;
+    }
     Ok(ApiResponse::ok(
         db::get_shorts(
             &conn,
             query.lang.unwrap_or(db::Lang::ZhCN),
-            query.sort.unwrap_or(content_metadata::Column::PublishedAt),
+            query.sort_column(),
             query.published,
-            query.limit.unwrap_or(100) as u32,
+            query.cursor()?.as_ref(),
+            query.limit.unwrap_or(100),
         )
         .await?,
     ))
@@ -138,24 +289,104 @@ async fn get_shorts(
 async fn get_short_by_slug(
     Extension(conn): Extension<DatabaseConnection>,
     Query(query): Query<UrlQuery>,
+    RawQuery(raw_query): RawQuery,
     Path((slug,)): Path<(String,)>,
-) -> Result<ApiResponse<db::Short>, ApiError> {
-    Ok(ApiResponse::ok(
-        db::get_short_by_slug(&conn, &slug, query.lang.unwrap_or(db::Lang::ZhCN))
+) -> Result<Response, ApiError> {
+    match db::get_short_by_slug(&conn, &slug, query.lang.unwrap_or(db::Lang::ZhCN)).await? {
+        Some(short) => Ok(ApiResponse::ok(short).into_response()),
+        None => redirect_old_slug(&conn, &slug, "gallery", raw_query)
             .await?
-            .ok_or_else(|| ApiError::not_found("Short not found"))?,
-    ))
+            .ok_or_else(|| ApiError::not_found("Short not found")),
+    }
 }
 
 async fn get_article_by_slug(
     Extension(conn): Extension<DatabaseConnection>,
     Query(query): Query<UrlQuery>,
+    RawQuery(raw_query): RawQuery,
     Path((slug,)): Path<(String,)>,
-) -> Result<ApiResponse<db::ArticleBySlug>, ApiError> {
-    let article = db::get_article_by_slug(&conn, &slug, query.lang.unwrap_or(db::Lang::ZhCN))
-        .await?
-        .ok_or_else(|| ApiError::not_found("Article not found"))?;
-    Ok(ApiResponse::ok(article))
+) -> Result<Response, ApiError> {
+    match db::get_article_by_slug(&conn, &slug, query.lang.unwrap_or(db::Lang::ZhCN)).await? {
+        Some(article) => Ok(ApiResponse::ok(article).into_response()),
+        None => redirect_old_slug(&conn, &slug, "article", raw_query)
+            .await?
+            .ok_or_else(|| ApiError::not_found("Article not found")),
+    }
+}
+
+/// 文章改名后，旧 slug 返回 301 并在响应体中给出新的 slug
+async fn redirect_old_slug(
+    conn: &DatabaseConnection,
+    slug: &str,
+    content_type: &str,
+    raw_query: Option<String>,
+) -> Result<Option<Response>, ApiError> {
+    let Some(new_slug) = db::get_slug_redirect(conn, slug, content_type).await? else {
+        return Ok(None);
+    };
+    let prefix = if content_type == "gallery" {
+        "shorts"
+    } else {
+        "articles"
+    };
+    // 借助 Url 对 slug 中的非 ASCII 字符进行百分号编码
+    let mut url = reqwest::Url::parse("http://localhost").context("Invalid base url")?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid base url"))?
+        .extend(["api", prefix, &new_slug]);
+    url.set_query(raw_query.as_deref());
+    let location = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_owned(),
+    };
+    let location = HeaderValue::from_str(&location).context("Invalid redirect location")?;
+    Ok(Some(
+        (
+            [(header::LOCATION, location)],
+            ApiResponse::moved_permanently(SlugRedirect { slug: new_slug }),
+        )
+            .into_response(),
+    ))
+}
+
+async fn get_related_articles(
+    Extension(conn): Extension<DatabaseConnection>,
+    Query(query): Query<UrlQuery>,
+    Query(related): Query<RelatedQuery>,
+    Path((slug,)): Path<(String,)>,
+) -> Result<ApiResponse<Vec<db::ArticleByList>>, ApiError> {
+    let (lang, limit) = (query.lang.unwrap_or(db::Lang::ZhCN), query.limit.unwrap_or(5));
+    if let RelatedBy::Embedding = related.by {
+        let articles = db::get_related_articles(&conn, &slug, lang, limit).await?;
+        // 文章尚未生成 embedding 时退化为基于标签的推荐
+        if !articles.is_empty() {
+            return Ok(ApiResponse::ok(articles));
+        }
+    }
+    Ok(ApiResponse::ok(
+        db::get_related_articles_by_tags(&conn, &slug, lang, limit).await?,
+    ))
+}
+
+async fn search_articles(
+    Extension(conn): Extension<DatabaseConnection>,
+    Query(query): Query<UrlQuery>,
+    Query(search): Query<SearchQuery>,
+) -> Result<ApiResponse<Vec<db::ArticleByList>>, ApiError> {
+    let keyword = search.q.trim();
+    if keyword.is_empty() {
+        return Err(ApiError::bad_request("Search keyword is empty"));
+    }
+    Ok(ApiResponse::ok(
+        db::search_articles(
+            &conn,
+            keyword,
+            search.mode,
+            query.lang.unwrap_or(db::Lang::ZhCN),
+            query.limit.unwrap_or(20),
+        )
+        .await?,
+    ))
 }
 
 async fn increase_view_count(
@@ -189,6 +420,23 @@ async fn get_tags_with_count(
     Ok(ApiResponse::ok(db::get_tags_with_count(&conn).await?))
 }
 
+async fn get_series_list(
+    Extension(conn): Extension<DatabaseConnection>,
+) -> Result<ApiResponse<Vec<db::SeriesWithCount>>, ApiError> {
+    Ok(ApiResponse::ok(db::get_series_list(&conn).await?))
+}
+
+async fn get_series_by_name(
+    Extension(conn): Extension<DatabaseConnection>,
+    Query(query): Query<UrlQuery>,
+    Path((name,)): Path<(String,)>,
+) -> Result<ApiResponse<db::SeriesDetail>, ApiError> {
+    let series = db::get_series_by_name(&conn, &name, query.lang.unwrap_or(db::Lang::ZhCN))
+        .await?
+        .ok_or_else(|| ApiError::not_found("Series not found"))?;
+    Ok(ApiResponse::ok(series))
+}
+
 async fn get_archives_group_by_year(
     Extension(conn): Extension<DatabaseConnection>,
     Query(query): Query<UrlQuery>,
@@ -202,13 +450,14 @@ async fn get_articles_by_tag(
     Extension(conn): Extension<DatabaseConnection>,
     Query(query): Query<UrlQuery>,
     Path((tag_name,)): Path<(String,)>,
-) -> Result<ApiResponse<Vec<db::ArticleByList>>, ApiError> {
+) -> Result<ApiResponse<db::Page<db::ArticleByList>>, ApiError> {
     Ok(ApiResponse::ok(
         db::get_articles_by_tag(
             &conn,
             &tag_name,
             query.lang.unwrap_or(db::Lang::ZhCN),
-            query.sort.unwrap_or(content_metadata::Column::PublishedAt),
+            query.sort_column(),
+            query.cursor()?.as_ref(),
             query.limit.unwrap_or(100),
         )
         .await?,
@@ -314,6 +563,68 @@ async fn delete_comment(
     Ok(ApiResponse::ok(()))
 }
 
+async fn get_scheduled_contents(
+    Extension(conn): Extension<DatabaseConnection>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<UrlQuery>,
+) -> Result<ApiResponse<Vec<db::ScheduledContent>>, ApiError> {
+    require_admin(&identity)?;
+    Ok(ApiResponse::ok(
+        db::get_scheduled_contents(&conn, query.lang.unwrap_or(db::Lang::ZhCN)).await?,
+    ))
+}
+
+async fn create_preview_link(
+    Extension(conn): Extension<DatabaseConnection>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<PreviewQuery>,
+    Path((slug,)): Path<(String,)>,
+) -> Result<ApiResponse<PreviewLink>, ApiError> {
+    require_admin(&identity)?;
+    if !db::preview_exists(&conn, &slug).await? {
+        return Err(ApiError::not_found("Draft not found"));
+    }
+    // 默认有效期为三天，最长不超过三十天
+    let ttl_hours = query.ttl.unwrap_or(72).clamp(1, 24 * 30);
+    let claims = PreviewClaims::of(slug, (ttl_hours * 3600) as usize);
+    let token = claims.encode();
+    Ok(ApiResponse::ok(PreviewLink {
+        url: format!("{}/preview/{}", CONFIG.host_url.trim_end_matches('/'), token),
+        expires_at: Local::now() + chrono::Duration::hours(ttl_hours as i64),
+        token,
+    }))
+}
+
+async fn get_preview(
+    Extension(conn): Extension<DatabaseConnection>,
+    Path((token,)): Path<(String,)>,
+) -> Result<ApiResponse<db::ArticleBySlug>, ApiError> {
+    let claims = PreviewClaims::decode(&token).map_err(|_| ApiError::forbidden("Invalid or expired preview link"))?;
+    let preview = db::get_preview_by_slug(&conn, &claims.slug)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Draft not found"))?;
+    Ok(ApiResponse::ok(preview))
+}
+
+async fn get_trashed_contents(
+    Extension(conn): Extension<DatabaseConnection>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<UrlQuery>,
+) -> Result<ApiResponse<Vec<db::TrashedContent>>, ApiError> {
+    require_admin(&identity)?;
+    Ok(ApiResponse::ok(
+        db::get_trashed_contents(&conn, query.lang.unwrap_or(db::Lang::ZhCN)).await?,
+    ))
+}
+
+pub(super) fn require_admin(identity: &Identity) -> Result<(), ApiError> {
+    match identity {
+        Identity::Admin { .. } => Ok(()),
+        Identity::Authenticated { .. } => Err(ApiError::forbidden("Admin only")),
+        _ => Err(ApiError::unauthorized("Login required")),
+    }
+}
+
 async fn get_comments_by_slug(
     Extension(conn): Extension<DatabaseConnection>,
     Path((slug,)): Path<(String,)>,
@@ -330,13 +641,154 @@ pub fn router() -> axum::Router {
         .route("/shorts/{slug}", get(get_short_by_slug))
         .route("/articles/{slug}", get(get_article_by_slug))
         .route("/articles/{slug}/views", post(increase_view_count))
+        .route("/articles/{slug}/related", get(get_related_articles))
         .route(
             "/articles/{slug}/comments",
             get(get_comments_by_slug).post(add_comment).delete(delete_comment),
         )
         .route("/articles/{slug}/likes", get(get_likes).post(like_content))
         .route("/tags", get(get_tags_with_count))
+        .route("/series", get(get_series_list))
+        .route("/series/{name}", get(get_series_by_name))
         .route("/archives", get(get_archives_group_by_year))
+        .route("/search", get(search_articles))
         .route("/tags/{tag_name}/articles", get(get_articles_by_tag))
+        .route("/preview/{token}", get(get_preview))
+        .route("/admin/scheduled", get(get_scheduled_contents))
+        .route("/admin/trash", get(get_trashed_contents))
+        .route("/admin/previews/{slug}", post(create_preview_link))
+        .route(
+            "/media",
+            post(media::upload_media).layer(DefaultBodyLimit::max(media::MAX_UPLOAD_SIZE)),
+        )
+        .route("/admin/markdown", post(authoring::create_markdown))
+        .route(
+            "/admin/markdown/{slug}",
+            get(authoring::get_markdown)
+                .put(authoring::update_markdown)
+                .delete(authoring::delete_markdown),
+        )
         .layer(axum::middleware::from_fn(middleware::auth))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{StatusCode, Uri};
+    use chrono::Timelike;
+    use suwen_markdown::MarkdownChange;
+
+    use super::*;
+
+    fn query(uri: &'static str) -> Result<UrlQuery, StatusCode> {
+        Query::<UrlQuery>::try_from_uri(&Uri::from_static(uri))
+            .map(|Query(query)| query)
+            .map_err(|e| e.status())
+    }
+
+    #[tokio::test]
+    async fn test_renamed_slug_redirects() {
+        let conn = db::test_connection().await;
+        for (slug, content_type) in [("redirect-article", "article"), ("redirect-short", "gallery")] {
+            content_metadata::Entity::insert(content_metadata::ActiveModel {
+                slug: Set(slug.to_owned()),
+                content_hash: Set(slug.to_owned()),
+                cover_images: Set(Vec::new().into()),
+                tags: Set(Vec::new().into()),
+                content_type: Set(content_type.to_owned()),
+                published_at: Set(Some(Local::now())),
+                ..Default::default()
+            })
+            .exec(&conn)
+            .await
+            .unwrap();
+            db::handle_markdown_change(
+                &conn,
+                MarkdownChange::Renamed(slug.to_owned(), format!("{slug}-renamed")),
+            )
+            .await
+            .unwrap();
+        }
+
+        let location = async |slug: &str, content_type: &str| {
+            redirect_old_slug(&conn, slug, content_type, Some("lang=en".to_owned()))
+                .await
+                .unwrap_or_else(|_| panic!("failed to look up redirect for {slug}"))
+                .map(|resp| resp.headers()[header::LOCATION].to_str().unwrap().to_owned())
+        };
+        assert_eq!(
+            location("redirect-article", "article").await.as_deref(),
+            Some("/api/articles/redirect-article-renamed?lang=en")
+        );
+        assert_eq!(
+            location("redirect-short", "gallery").await.as_deref(),
+            Some("/api/shorts/redirect-short-renamed?lang=en")
+        );
+        // 旧 slug 只在同类型的内容之间跳转
+        assert!(location("redirect-article", "gallery").await.is_none());
+        assert!(location("redirect-short", "article").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unpublished_list_requires_admin() {
+        use tower::ServiceExt;
+
+        let app = router().layer(Extension(db::test_connection().await));
+        let status = async |uri: &str| {
+            let request = axum::http::Request::get(uri).body(axum::body::Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap().status()
+        };
+        assert_eq!(status("/articles?published=false").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/shorts?published=false").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/articles?published=true").await, StatusCode::OK);
+        assert_eq!(status("/shorts").await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_split_list() {
+        let value = serde_json::json!(" rust, ,web,, 随笔 ");
+        assert_eq!(split_list(&value), vec!["rust", "web", "随笔"]);
+        assert!(split_list(&serde_json::json!("")).is_empty());
+        assert!(split_list(&serde_json::json!(1)).is_empty());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let from = parse_date("2025-03-01", false).unwrap();
+        assert_eq!((from.hour(), from.minute(), from.second()), (0, 0, 0));
+        let to = parse_date("2025-03-01", true).unwrap();
+        assert_eq!((to.hour(), to.minute(), to.second()), (23, 59, 59));
+        assert_eq!(from.date_naive(), to.date_naive());
+
+        let datetime = parse_date("2025-03-01T08:00:00Z", true).unwrap();
+        assert_eq!(datetime, DateTime::parse_from_rfc3339("2025-03-01T08:00:00Z").unwrap());
+
+        assert!(parse_date("2025-13-01", false).is_none());
+        assert!(parse_date("yesterday", false).is_none());
+    }
+
+    #[test]
+    fn test_limit_is_clamped() {
+        assert_eq!(
+            query("/articles?limit=18446744073709551615").unwrap().limit,
+            Some(MAX_LIMIT)
+        );
+        assert_eq!(query("/articles?limit=20").unwrap().limit, Some(20));
+    }
+
+    #[test]
+    fn test_query_filter() {
+        let query = query("/articles?tags=a,b&tag_match=all&exclude_tags=c&from=2025-01-01&to=&type=short").unwrap();
+        assert_eq!(query.filter.tags, vec!["a", "b"]);
+        assert!(query.filter.match_all_tags);
+        assert_eq!(query.filter.exclude_tags, vec!["c"]);
+        assert_eq!(query.filter.from, parse_date("2025-01-01", false));
+        assert!(query.filter.to.is_none());
+        assert_eq!(query.filter.content_type, Some("gallery"));
+    }
+
+    #[test]
+    fn test_malformed_date_is_rejected() {
+        assert_eq!(query("/articles?from=2025-02-30").err(), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(query("/articles?to=tomorrow").err(), Some(StatusCode::BAD_REQUEST));
+    }
+}
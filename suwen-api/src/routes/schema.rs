@@ -53,6 +53,15 @@ impl From<(identity::Model, Option<user::Model>)> for IdentityInfo {
                 is_anonymous: false,
                 is_admin: user.id == 1,
             }
+        } else if let Some(display_name) = identity.display_name {
+            // 导入的评论者，保留原平台的昵称
+            Self {
+                id: identity.id,
+                avatar_url: None,
+                display_name,
+                is_anonymous: true,
+                is_admin: false,
+            }
         } else if let Some(uuid) = identity.uuid {
             Self {
                 id: identity.id,
@@ -0,0 +1,167 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use suwen_config::CONFIG;
+use tokio::sync::Notify;
+
+use crate::db::{self, ScheduledContent};
+use crate::media_gc::collect_orphaned_media;
+
+const MAX_WAIT: Duration = Duration::from_secs(60);
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+static RESCHEDULE: Notify = Notify::const_new();
+
+/// 内容的发布时间可能发生了变化，唤醒调度器重新计算下一次检查时间
+pub(crate) fn reschedule() {
+    RESCHEDULE.notify_one();
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishedEvent<'a> {
+    event: &'static str,
+    slug: &'a str,
+    title: &'a str,
+    content_type: &'a str,
+    url: String,
+    published_at: DateTime<Local>,
+}
+
+/// 在定时发布的内容到达发布时间时刷新缓存并发送 webhook 通知
+pub async fn run_publish_scheduler(conn: DatabaseConnection) {
+    // 从上次停止时检查到的时间继续，首次运行时没有记录，不为已有的内容补发通知
+    let mut last_check = match db::get_publish_checked_at(&conn).await {
+        Ok(checked_at) => checked_at.unwrap_or_else(Local::now),
+        Err(e) => {
+            error!("Failed to query last publish check time: {}", e);
+            Local::now()
+        }
+    };
+    loop {
+        let wait = match db::get_next_scheduled_at(&conn).await {
+            Ok(Some(next)) => (next - Local::now()).to_std().unwrap_or_default().min(MAX_WAIT),
+            Ok(None) => MAX_WAIT,
+            Err(e) => {
+                error!("Failed to query next scheduled content: {}", e);
+                MAX_WAIT
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = RESCHEDULE.notified() => {}
+        }
+        let now = Local::now();
+        match db::get_contents_published_between(&conn, CONFIG.source_lang, last_check, now).await {
+            Ok(contents) => {
+                last_check = now;
+                if let Err(e) = db::set_publish_checked_at(&conn, now).await {
+                    error!("Failed to save publish check time: {}", e);
+                }
+                if contents.is_empty() {
+                    continue;
+                }
+                db::invalidate_related_cache();
+                for content in &contents {
+                    info!("Scheduled content published: {}", content.slug);
+                    notify_published(content).await;
+                }
+            }
+            Err(e) => {
+                error!("Failed to query published contents: {}", e);
+            }
+        }
+    }
+}
+
+/// 定期彻底删除回收站中超过保留期限的内容
+pub async fn run_trash_purger(conn: DatabaseConnection) {
+    loop {
+        match db::purge_expired_trash(&conn).await {
+            Ok(0) => {}
+            Ok(count) => info!("Purged {} expired contents from trash", count),
+            Err(e) => error!("Failed to purge expired trash: {}", e),
+        }
+        tokio::time::sleep(PURGE_INTERVAL).await;
+    }
+}
+
+/// 按配置的间隔定期清理不再被引用的媒体文件
+pub async fn run_media_gc(conn: DatabaseConnection) {
+    let Some(interval_hours) = CONFIG.media_gc.interval_hours else {
+        return;
+    };
+    let interval = Duration::from_secs(interval_hours.max(1) * 60 * 60);
+    loop {
+        tokio::time::sleep(interval).await;
+        match collect_orphaned_media(&conn, false).await {
+            Ok(keys) if keys.is_empty() => {}
+            Ok(keys) => info!("Deleted {} orphaned media files", keys.len()),
+            Err(e) => error!("Failed to collect orphaned media: {}", e),
+        }
+    }
+}
+
+async fn notify_published(content: &ScheduledContent) {
+    static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap()
+    });
+    let event = PublishedEvent {
+        event: "published",
+        slug: &content.slug,
+        title: &content.title,
+        content_type: &content.content_type,
+        url: content_url(&CONFIG.host_url, content),
+        published_at: content.published_at,
+    };
+    for webhook in &CONFIG.publish_webhooks {
+        let result: Result<()> = async {
+            CLIENT.post(webhook).json(&event).send().await?.error_for_status()?;
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("Failed to notify webhook {} for {}: {}", webhook, content.slug, e);
+        }
+    }
+}
+
+/// 短动态在数据库中的类型为 gallery，对外的路径为 /shorts
+fn content_url(host_url: &str, content: &ScheduledContent) -> String {
+    let path = if content.content_type == "gallery" {
+        "shorts"
+    } else {
+        "articles"
+    };
+    format!("{}/{}/{}", host_url.trim_end_matches('/'), path, content.slug)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_url() {
+        let content = |content_type: &str| ScheduledContent {
+            slug: "hello".to_owned(),
+            title: "Hello".to_owned(),
+            content_type: content_type.to_owned(),
+            published_at: Local::now(),
+        };
+        assert_eq!(
+            content_url("https://example.com/", &content("gallery")),
+            "https://example.com/shorts/hello"
+        );
+        assert_eq!(
+            content_url("https://example.com", &content("article")),
+            "https://example.com/articles/hello"
+        );
+    }
+}
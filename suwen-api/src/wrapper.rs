@@ -24,6 +24,14 @@ impl<T: Serialize> ApiResponse<T> {
         }
     }
 
+    pub fn moved_permanently(data: T) -> Self {
+        Self {
+            status_code: 301,
+            data: Some(data),
+            message: None,
+        }
+    }
+
     pub fn bad_request(message: impl Into<Cow<'static, str>>) -> Self {
         Self {
             status_code: 400,
@@ -56,6 +64,14 @@ impl<T: Serialize> ApiResponse<T> {
         }
     }
 
+    pub fn conflict(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            status_code: 409,
+            data: None,
+            message: Some(message.into()),
+        }
+    }
+
     pub fn internal_server_error(message: impl Into<Cow<'static, str>>) -> Self {
         Self {
             status_code: 500,
@@ -1,5 +1,6 @@
 use std::fmt::{Display, Formatter};
 use std::fs;
+use std::path::PathBuf;
 use std::sync::LazyLock;
 
 use anyhow::{Context, Result};
@@ -21,12 +22,39 @@ pub struct Config {
     pub openai_base_url: Option<String>,
     pub openai_api_key: String,
     pub openai_model: String,
+    #[serde(default)]
+    pub openai_embedding_model: Option<String>,
+    /// 支持图像输入的模型，配置后为缺少替代文本的图片生成描述
+    #[serde(default)]
+    pub openai_vision_model: Option<String>,
     pub host_url: String,
     pub r2: R2Config,
+    /// 媒体文件的存储位置，默认使用 r2 配置
+    #[serde(default)]
+    pub media_store: MediaStoreConfig,
+    /// 上传图片时生成的响应式版本
+    #[serde(default)]
+    pub responsive_images: ResponsiveImageConfig,
+    /// 清理不再被内容引用的媒体文件
+    #[serde(default)]
+    pub media_gc: MediaGcConfig,
     #[serde(default)]
     pub markdown_path: Option<String>,
     #[serde(default)]
     pub source_lang: Lang,
+    /// 内容到达发布时间时通知的 webhook 地址
+    #[serde(default)]
+    pub publish_webhooks: Vec<String>,
+    /// 被删除的内容在回收站中保留的天数，期间同名 slug 重新出现时会连同评论、点赞一起恢复
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// 从 git 仓库同步内容，配置后取代 markdown_path 的文件监听
+    #[serde(default)]
+    pub git_source: Option<GitSourceConfig>,
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -40,10 +68,139 @@ pub struct R2Config {
     pub s3_domain: String,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GitSourceConfig {
+    /// 仓库地址，可以是本地路径或 file:// 等 git 支持的任意远程地址
+    pub url: String,
+    /// 同步的分支，未设置时使用远程仓库的默认分支
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// 仓库中存放 markdown 的子目录，未设置时同步整个仓库
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 本地工作副本的位置，默认位于配置目录下
+    #[serde(default)]
+    pub checkout_path: Option<String>,
+    #[serde(default = "default_git_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_git_poll_interval_secs() -> u64 {
+    60
+}
+
+impl GitSourceConfig {
+    pub fn checkout_path(&self) -> PathBuf {
+        match &self.checkout_path {
+            Some(path) => PathBuf::from(path),
+            None => config_dir()
+                .map(|path| path.join("suwen").join("content"))
+                .unwrap_or_else(|| "content".into()),
+        }
+    }
+}
+
 fn default_s3_domain() -> String {
     "https://obj.amto.cc".to_string()
 }
 
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MediaStoreConfig {
+    /// Cloudflare R2，使用顶层的 r2 配置
+    #[default]
+    R2,
+    /// 任意兼容 S3 协议的对象存储
+    S3(S3Config),
+    /// 存储在本地目录，由 /uploads 提供访问
+    Local(LocalStoreConfig),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    pub bucket_name: String,
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    #[serde(default)]
+    pub prefix: String,
+    /// 使用 endpoint/bucket/key 形式的地址，MinIO 等自建服务通常需要开启
+    #[serde(default)]
+    pub path_style: bool,
+    /// 对外访问文件的地址前缀
+    pub public_url: String,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LocalStoreConfig {
+    /// 存储目录，未设置时使用环境变量 UPLOAD_DIR 或 uploads
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 对外访问文件的地址前缀，未设置时使用 {host_url}/uploads
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ResponsiveImageConfig {
+    /// 生成的图片宽度，大于等于原图宽度的不会生成
+    #[serde(default = "default_image_widths")]
+    pub widths: Vec<u32>,
+    /// 额外生成 AVIF 版本，编码较慢
+    #[serde(default)]
+    pub avif: bool,
+    /// 渲染图片时使用的 sizes 属性
+    #[serde(default = "default_image_sizes")]
+    pub sizes: String,
+}
+
+impl Default for ResponsiveImageConfig {
+    fn default() -> Self {
+        Self {
+            widths: default_image_widths(),
+            avif: false,
+            sizes: default_image_sizes(),
+        }
+    }
+}
+
+fn default_image_widths() -> Vec<u32> {
+    vec![480, 960, 1600]
+}
+
+fn default_image_sizes() -> String {
+    "(max-width: 1024px) 100vw, 1024px".to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MediaGcConfig {
+    /// 定期清理的间隔小时数，未设置时只能通过 suwen media gc 手动清理
+    #[serde(default)]
+    pub interval_hours: Option<u64>,
+    /// 上传后未满该小时数的文件不会被清理，避免删除编辑器中刚上传、尚未保存到文章的媒体
+    #[serde(default = "default_media_gc_grace_hours")]
+    pub grace_hours: u64,
+}
+
+impl Default for MediaGcConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: None,
+            grace_hours: default_media_gc_grace_hours(),
+        }
+    }
+}
+
+fn default_media_gc_grace_hours() -> u64 {
+    24
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 pub enum Lang {
     #[default]
@@ -85,10 +242,18 @@ impl Default for Config {
             openai_api_key: String::new(),
             openai_base_url: None,
             openai_model: String::new(),
+            openai_embedding_model: None,
+            openai_vision_model: None,
             host_url: String::new(),
             r2: R2Config::default(),
+            media_store: MediaStoreConfig::default(),
+            responsive_images: ResponsiveImageConfig::default(),
+            media_gc: MediaGcConfig::default(),
             markdown_path: None,
             source_lang: Default::default(),
+            publish_webhooks: Vec::new(),
+            trash_retention_days: default_trash_retention_days(),
+            git_source: None,
         }
     }
 }
@@ -19,6 +19,7 @@ pub struct Model {
     pub parent_id: Option<i32>,
     pub content: String,
     pub is_deleted: bool,
+    pub source_id: Option<String>,
     pub created_at: DateTimeLocal,
     pub updated_at: DateTimeLocal,
 }
@@ -31,6 +32,7 @@ pub enum Column {
     ParentId,
     Content,
     IsDeleted,
+    SourceId,
     CreatedAt,
     UpdatedAt,
 }
@@ -63,6 +65,7 @@ impl ColumnTrait for Column {
             Self::ParentId => ColumnType::Integer.def().null(),
             Self::Content => ColumnType::Text.def(),
             Self::IsDeleted => ColumnType::Boolean.def().default(false),
+            Self::SourceId => ColumnType::Text.def().unique().null(),
             Self::CreatedAt => ColumnType::DateTime.def(),
             Self::UpdatedAt => ColumnType::DateTime.def(),
         }
@@ -28,6 +28,11 @@ pub struct Model {
     pub published_at: Option<DateTimeLocal>,
     pub created_at: DateTimeLocal,
     pub updated_at: DateTimeLocal,
+    pub series_id: Option<i32>,
+    pub series_order: Option<i32>,
+    pub deleted_at: Option<DateTimeLocal>,
+    pub source_commit: Option<String>,
+    pub unpublished_at: Option<DateTimeLocal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -45,6 +50,11 @@ pub enum Column {
     PublishedAt,
     CreatedAt,
     UpdatedAt,
+    SeriesId,
+    SeriesOrder,
+    DeletedAt,
+    SourceCommit,
+    UnpublishedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -62,7 +72,10 @@ impl PrimaryKeyTrait for PrimaryKey {
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {
     Content,
+    ContentEmbedding,
     ContentMetadataTag,
+    Series,
+    SlugRedirect,
 }
 
 impl ColumnTrait for Column {
@@ -82,6 +95,11 @@ impl ColumnTrait for Column {
             Self::PublishedAt => ColumnType::DateTime.def().null(),
             Self::CreatedAt => ColumnType::DateTime.def(),
             Self::UpdatedAt => ColumnType::DateTime.def(),
+            Self::SeriesId => ColumnType::Integer.def().null(),
+            Self::SeriesOrder => ColumnType::Integer.def().null(),
+            Self::DeletedAt => ColumnType::DateTime.def().null(),
+            Self::SourceCommit => ColumnType::Text.def().null(),
+            Self::UnpublishedAt => ColumnType::DateTime.def().null(),
         }
     }
 }
@@ -90,7 +108,13 @@ impl RelationTrait for Relation {
     fn def(&self) -> RelationDef {
         match self {
             Self::Content => Entity::has_many(super::content::Entity).into(),
+            Self::ContentEmbedding => Entity::has_one(super::content_embedding::Entity).into(),
             Self::ContentMetadataTag => Entity::has_many(super::content_metadata_tag::Entity).into(),
+            Self::Series => Entity::belongs_to(super::series::Entity)
+                .from(Column::SeriesId)
+                .to(super::series::Column::Id)
+                .into(),
+            Self::SlugRedirect => Entity::has_many(super::slug_redirect::Entity).into(),
         }
     }
 }
@@ -101,10 +125,28 @@ impl Related<super::content::Entity> for Entity {
     }
 }
 
+impl Related<super::content_embedding::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ContentEmbedding.def()
+    }
+}
+
 impl Related<super::content_metadata_tag::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ContentMetadataTag.def()
     }
 }
 
+impl Related<super::series::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Series.def()
+    }
+}
+
+impl Related<super::slug_redirect::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SlugRedirect.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -16,6 +16,7 @@ pub struct Model {
     pub id: i32,
     pub uuid: Option<Uuid>,
     pub user_id: Option<i32>,
+    pub display_name: Option<String>,
     pub created_at: DateTimeLocal,
     pub updated_at: DateTimeLocal,
 }
@@ -25,6 +26,7 @@ pub enum Column {
     Id,
     Uuid,
     UserId,
+    DisplayName,
     CreatedAt,
     UpdatedAt,
 }
@@ -55,6 +57,7 @@ impl ColumnTrait for Column {
             Self::Id => ColumnType::Integer.def(),
             Self::Uuid => ColumnType::Text.def().unique().null(),
             Self::UserId => ColumnType::Integer.def().null(),
+            Self::DisplayName => ColumnType::Text.def().null(),
             Self::CreatedAt => ColumnType::DateTime.def(),
             Self::UpdatedAt => ColumnType::DateTime.def(),
         }
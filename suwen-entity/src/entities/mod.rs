@@ -7,18 +7,26 @@ pub mod prelude;
 
 pub mod comment;
 pub mod content;
+pub mod content_embedding;
 pub mod content_metadata;
 pub mod content_metadata_tag;
+pub mod content_preview;
 pub mod identity;
+pub mod image_alt_text;
 pub mod like;
+pub mod media;
+pub mod series;
 pub mod site;
+pub mod slug_redirect;
 pub mod user;
 
 pub use content::{Toc, TocItem};
+pub use content_embedding::Vector;
+pub use media::{MediaVariant, MediaVariants};
 pub use site::{RelatedLink, RelatedLinks, Tab, Tabs};
 
 // Reference: https://www.sea-ql.org/SeaORM/docs/generate-entity/column-types/#json-column
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult, Default)]
 pub struct VecString(pub Vec<String>);
 
 impl From<Vec<String>> for VecString {
@@ -2,9 +2,15 @@
 
 pub use super::comment::Entity as Comment;
 pub use super::content::Entity as Content;
+pub use super::content_embedding::Entity as ContentEmbedding;
 pub use super::content_metadata::Entity as ContentMetadata;
 pub use super::content_metadata_tag::Entity as ContentMetadataTag;
+pub use super::content_preview::Entity as ContentPreview;
 pub use super::identity::Entity as Identity;
+pub use super::image_alt_text::Entity as ImageAltText;
 pub use super::like::Entity as Like;
+pub use super::media::Entity as Media;
+pub use super::series::Entity as Series;
 pub use super::site::Entity as Site;
+pub use super::slug_redirect::Entity as SlugRedirect;
 pub use super::user::Entity as User;
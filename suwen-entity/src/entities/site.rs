@@ -44,6 +44,7 @@ pub struct Model {
     pub related_links: RelatedLinks,
     pub created_at: DateTimeLocal,
     pub updated_at: DateTimeLocal,
+    pub publish_checked_at: Option<DateTimeLocal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -56,6 +57,7 @@ pub enum Column {
     RelatedLinks,
     CreatedAt,
     UpdatedAt,
+    PublishCheckedAt,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DerivePrimaryKey)]
@@ -87,6 +89,7 @@ impl ColumnTrait for Column {
             Self::RelatedLinks => ColumnType::Text.def(),
             Self::CreatedAt => ColumnType::DateTime.def(),
             Self::UpdatedAt => ColumnType::DateTime.def(),
+            Self::PublishCheckedAt => ColumnType::DateTime.def().null(),
         }
     }
 }
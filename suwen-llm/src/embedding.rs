@@ -0,0 +1,166 @@
+use std::hash::Hasher;
+
+use anyhow::{Context, Result};
+use llm::builder::{LLMBackend, LLMBuilder};
+use suwen_config::CONFIG;
+use suwen_markdown::Markdown;
+use twox_hash::XxHash3_64;
+
+/// 本地 embedding 使用的模型名，与远程模型生成的向量互不可比
+pub const LOCAL_EMBEDDING_MODEL: &str = "local-hash-v1";
+
+const LOCAL_EMBEDDING_DIMENSIONS: usize = 256;
+// 输入文本的最大字符数，避免超出 embedding 模型的上下文限制
+const MAX_INPUT_CHARS: usize = 6000;
+
+pub struct Embedding {
+    pub model: String,
+    pub vector: Vec<f32>,
+}
+
+pub async fn generate_article_embedding(article: &Markdown) -> Result<Embedding> {
+    generate_embedding(&format!(
+        "{}\n{}\n{}",
+        article.title(),
+        article.tags().join(", "),
+        article.content()
+    ))
+    .await
+}
+
+/// 优先使用配置的远程模型生成 embedding，未配置或请求失败时退化为本地的确定性 embedding
+pub async fn generate_embedding(text: &str) -> Result<Embedding> {
+    let text = text.chars().take(MAX_INPUT_CHARS).collect::<String>();
+    if let Some(model) = &CONFIG.openai_embedding_model
+        && !CONFIG.openai_api_key.is_empty()
+    {
+        match remote_embedding(model, &text).await {
+            Ok(vector) => {
+                return Ok(Embedding {
+                    model: model.clone(),
+                    vector,
+                });
+            }
+            Err(e) => {
+                warn!("Failed to generate remote embedding, falling back to local: {}", e);
+            }
+        }
+    }
+    Ok(Embedding {
+        model: LOCAL_EMBEDDING_MODEL.to_owned(),
+        vector: local_embedding(&text),
+    })
+}
+
+async fn remote_embedding(model: &str, text: &str) -> Result<Vec<f32>> {
+    let mut llm = LLMBuilder::new()
+        .backend(LLMBackend::OpenAI)
+        .api_key(&CONFIG.openai_api_key)
+        .model(model)
+        .timeout_seconds(60);
+    if let Some(base_url) = &CONFIG.openai_base_url {
+        llm = llm.base_url(base_url);
+    }
+    let llm = llm.build()?;
+    llm.embed(vec![text.to_owned()])
+        .await?
+        .into_iter()
+        .next()
+        .context("Empty embedding response")
+}
+
+/// 基于特征哈希的本地 embedding：英文按单词切分，中日韩文字按单字与二元组切分
+pub fn local_embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0f32; LOCAL_EMBEDDING_DIMENSIONS];
+    for token in tokenize(text) {
+        let mut hasher = XxHash3_64::default();
+        hasher.write(token.as_bytes());
+        let hash = hasher.finish();
+        let index = (hash % LOCAL_EMBEDDING_DIMENSIONS as u64) as usize;
+        // 使用哈希的最高位决定符号，降低碰撞带来的偏差
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[index] += sign;
+    }
+    normalize(&mut vector);
+    vector
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0f32, 0f32, 0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let (mut word, mut prev_cjk) = (String::new(), None::<char>);
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            tokens.push(c.to_string());
+            if let Some(prev) = prev_cjk {
+                tokens.push(format!("{}{}", prev, c));
+            }
+            prev_cjk = Some(c);
+        } else {
+            prev_cjk = None;
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}' // 平假名、片假名
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{ac00}'..='\u{d7af}' // 谚文
+        | '\u{f900}'..='\u{faff}')
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_embedding_is_deterministic() {
+        let text = "使用 Rust 编写博客系统";
+        assert_eq!(local_embedding(text), local_embedding(text));
+        assert!((cosine_similarity(&local_embedding(text), &local_embedding(text)) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_local_embedding_similarity() {
+        let base = local_embedding("Rust 异步编程与 tokio 运行时");
+        let similar = local_embedding("tokio 运行时下的 Rust 异步编程实践");
+        let unrelated = local_embedding("周末去公园拍摄樱花的摄影记录");
+        assert!(cosine_similarity(&base, &similar) > cosine_similarity(&base, &unrelated));
+    }
+}
@@ -1,9 +1,20 @@
+#[macro_use]
+extern crate tracing;
+
 use anyhow::Result;
 use llm::builder::{LLMBackend, LLMBuilder};
 use llm::chat::ChatMessage;
 use suwen_config::CONFIG;
 use suwen_markdown::Markdown;
+mod embedding;
 mod utils;
+mod vision;
+
+pub use embedding::{
+    Embedding, LOCAL_EMBEDDING_MODEL, cosine_similarity, generate_article_embedding, generate_embedding,
+    local_embedding,
+};
+pub use vision::generate_alt_text;
 
 static PROMPT: &str = "
 你是一个专业的博客文章摘要生成器，你的任务是提炼文章的核心观点和主要论据。生成的摘要应语气专业、流畅自然，如同人类撰写的导读，避免生硬的堆砌或句式重复。
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use llm::builder::{LLMBackend, LLMBuilder};
+use llm::chat::ChatMessage;
+use suwen_config::CONFIG;
+
+static ALT_TEXT_PROMPT: &str = "
+你是一个为博客图片撰写替代文本（alt text）的助手，替代文本会提供给屏幕阅读器以及无法加载图片的读者。
+请用一句简洁的中文客观描述图片的主要内容，长度控制在 50 字以内；如果图片中有关键文字，请一并概括。
+不要以“图片显示”“这是一张”等词语开头，不要使用任何格式，仅输出描述本身。
+";
+
+/// 替代文本的最大字符数，超出部分会被截断
+const MAX_ALT_TEXT_CHARS: usize = 120;
+
+/// 使用支持图像输入的模型为图片生成替代文本，返回所用模型与描述；未配置视觉模型时返回 None
+pub async fn generate_alt_text(image_url: &str) -> Result<Option<(String, String)>> {
+    let Some(model) = &CONFIG.openai_vision_model else {
+        return Ok(None);
+    };
+    let mut llm = LLMBuilder::new()
+        .backend(LLMBackend::OpenAI)
+        .system(ALT_TEXT_PROMPT)
+        .api_key(&CONFIG.openai_api_key)
+        .model(model)
+        .timeout_seconds(60)
+        .temperature(0.2);
+    if let Some(base_url) = &CONFIG.openai_base_url {
+        llm = llm.base_url(base_url);
+    }
+    let llm = llm.build()?;
+    let msgs = vec![
+        ChatMessage::user().content("请为这张图片生成替代文本").build(),
+        ChatMessage::user().image_url(image_url).build(),
+    ];
+    let alt_text = llm.chat(&msgs).await?.text().map(|text| {
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(MAX_ALT_TEXT_CHARS)
+            .collect::<String>()
+    });
+    Ok(alt_text
+        .filter(|text| !text.is_empty())
+        .map(|text| (model.clone(), text)))
+}
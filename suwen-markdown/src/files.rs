@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use suwen_config::CONFIG;
+
+use crate::Markdown;
+
+/// 列出目录下所有非隐藏的 markdown 文件，层级更浅的文件排在前面，slug 冲突时由其胜出
+pub async fn markdown_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if is_hidden(root, &path) {
+                continue;
+            }
+            if entry.file_type().await?.is_dir() {
+                dirs.push(path);
+            } else if is_markdown(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort_by_key(|path| (path.components().count(), path.clone()));
+    Ok(paths)
+}
+
+/// 查找 slug 对应的 markdown 文件，返回文件路径与未经处理的内容
+pub async fn find_markdown_file(root: &Path, slug: &str) -> Result<Option<(PathBuf, Markdown)>> {
+    for path in markdown_files(root).await? {
+        match Markdown::from_file(&path, CONFIG.source_lang).await {
+            Ok(markdown) if markdown.slug() == slug => return Ok(Some((path, markdown))),
+            Ok(_) => {}
+            Err(e) => debug!("Skipping invalid markdown file {:?}: {}", path, e),
+        }
+    }
+    Ok(None)
+}
+
+pub(crate) fn is_markdown(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+/// 忽略目录下以 . 开头的文件与目录（如 .git）
+pub(crate) fn is_hidden(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|c| c.as_os_str().to_str().is_some_and(|s| s.starts_with('.')))
+}
@@ -0,0 +1,379 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local};
+use futures::stream::BoxStream;
+use suwen_config::{CONFIG, GitSourceConfig};
+use tokio::sync::mpsc;
+
+use crate::source::spawn_source;
+use crate::{ContentSource, Markdown, MarkdownChange, MarkdownProcessor};
+
+// 记录最后一次同步的提交，存放在 .git 目录内以免被工作区的变更覆盖
+const SYNCED_COMMIT_FILE: &str = "suwen-synced-commit";
+
+/// 从 git 仓库同步内容：首次同步导入全部文件，之后按上次同步的提交与 HEAD 的差异产生变更
+pub struct GitSource {
+    config: GitSourceConfig,
+}
+
+impl GitSource {
+    pub fn new(config: GitSourceConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ContentSource for GitSource {
+    fn changes(self: Box<Self>) -> BoxStream<'static, MarkdownChange> {
+        spawn_source("Git content source", |sender| {
+            GitSync::new(self.config, sender).start_syncing()
+        })
+    }
+}
+
+struct GitSync {
+    config: GitSourceConfig,
+    repo_path: PathBuf,
+    db_sender: mpsc::UnboundedSender<MarkdownChange>,
+}
+
+impl GitSync {
+    fn new(config: GitSourceConfig, db_sender: mpsc::UnboundedSender<MarkdownChange>) -> Self {
+        let repo_path = config.checkout_path();
+        Self {
+            config,
+            repo_path,
+            db_sender,
+        }
+    }
+
+    async fn start_syncing(self) -> Result<()> {
+        self.prepare().await?;
+        let interval = Duration::from_secs(self.config.poll_interval_secs.max(1));
+        loop {
+            if let Err(e) = self.sync().await {
+                warn!("Failed to sync git repository {}: {}", self.config.url, e);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        if self.repo_path.join(".git").exists() {
+            return Ok(());
+        }
+        info!("Cloning {} into {:?}", self.config.url, self.repo_path);
+        if let Some(parent) = self.repo_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut args = vec!["clone", "--quiet"];
+        if let Some(branch) = &self.config.branch {
+            args.extend(["--branch", branch]);
+        }
+        let repo_path = self.repo_path.to_str().context("Invalid checkout path")?;
+        args.extend(["--", &self.config.url, repo_path]);
+        run_git(None, &args).await?;
+        Ok(())
+    }
+
+    async fn sync(&self) -> Result<()> {
+        self.pull().await?;
+        let head = self.git(&["rev-parse", "HEAD"]).await?;
+        let last = self.last_synced_commit().await;
+        match last {
+            Some(last) if last == head => return Ok(()),
+            // 上次同步的提交可能因强制推送而不复存在，此时退化为全量同步
+            Some(last) if self.commit_exists(&last).await => {
+                info!("Syncing git changes {}..{}", short(&last), short(&head));
+                self.sync_diff(&last, &head).await?;
+            }
+            _ => {
+                info!("Syncing all markdown files at {}", short(&head));
+                self.sync_all().await?;
+            }
+        }
+        tokio::fs::write(self.repo_path.join(".git").join(SYNCED_COMMIT_FILE), &head).await?;
+        Ok(())
+    }
+
+    /// 工作副本只读，直接重置到远程分支以兼容强制推送
+    async fn pull(&self) -> Result<()> {
+        self.git(&["fetch", "--quiet", "--prune", "origin"]).await?;
+        let target = match &self.config.branch {
+            Some(branch) => format!("origin/{branch}"),
+            None => "origin/HEAD".to_owned(),
+        };
+        self.git(&["reset", "--quiet", "--hard", &target]).await?;
+        Ok(())
+    }
+
+    async fn commit_exists(&self, commit: &str) -> bool {
+        self.git(&["cat-file", "-e", &format!("{commit}^{{commit}}")])
+            .await
+            .is_ok()
+    }
+
+    async fn last_synced_commit(&self) -> Option<String> {
+        let path = self.repo_path.join(".git").join(SYNCED_COMMIT_FILE);
+        let commit = tokio::fs::read_to_string(path).await.ok()?;
+        Some(commit.trim().to_owned()).filter(|c| !c.is_empty())
+    }
+
+    async fn sync_all(&self) -> Result<()> {
+        let output = self.git(&["ls-files", "-z", "--", self.content_dir()]).await?;
+        let mut paths = output.split('\0').filter(|p| is_content(p)).collect::<Vec<_>>();
+        // 与文件监听一致，slug 冲突时由层级更浅的文件胜出
+        paths.sort_by_key(|path| (path.matches('/').count(), *path));
+        let mut existing_slugs = HashSet::new();
+        for path in paths {
+            match self.process_file(path).await {
+                Some(markdown) if !existing_slugs.insert(markdown.slug().to_owned()) => {
+                    warn!(
+                        "Slug collision: {} is used by multiple files, skipping {}",
+                        markdown.slug(),
+                        path
+                    );
+                }
+                Some(markdown) => {
+                    let _ = self.db_sender.send(MarkdownChange::from(markdown));
+                }
+                // 解析失败时保留以文件名为 slug 的已有文章，避免被同步清理
+                None => existing_slugs.extend(file_stem(path)),
+            }
+        }
+        let _ = self
+            .db_sender
+            .send(MarkdownChange::SyncExisting(existing_slugs.into_iter().collect()));
+        Ok(())
+    }
+
+    async fn sync_diff(&self, last: &str, head: &str) -> Result<()> {
+        let output = self
+            .git(&[
+                "diff",
+                "--name-status",
+                "-z",
+                "-M",
+                last,
+                head,
+                "--",
+                self.content_dir(),
+            ])
+            .await?;
+        let mut fields = output.split('\0').filter(|f| !f.is_empty());
+        while let Some(status) = fields.next() {
+            let (old_path, new_path) = match status.chars().next() {
+                Some('R') | Some('C') => (fields.next(), fields.next()),
+                Some('D') => (fields.next(), None),
+                _ => (None, fields.next()),
+            };
+            // 复制产生的是新文件，原文件保持不变
+            let old_path = old_path.filter(|p| !status.starts_with('C') && is_content(p));
+            let new_path = new_path.filter(|p| is_content(p));
+            let old_slug = match old_path {
+                Some(path) => self.slug_at(last, path).await,
+                None => None,
+            };
+            match new_path {
+                Some(path) => {
+                    let Some(markdown) = self.process_file(path).await else {
+                        continue;
+                    };
+                    if let Some(old_slug) = old_slug
+                        && old_slug != markdown.slug()
+                    {
+                        let _ = self
+                            .db_sender
+                            .send(MarkdownChange::Renamed(old_slug, markdown.slug().to_owned()));
+                    }
+                    let _ = self.db_sender.send(MarkdownChange::from(markdown));
+                }
+                None => {
+                    if let Some(old_slug) = old_slug {
+                        let _ = self.db_sender.send(MarkdownChange::Deleted(old_slug));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn process_file(&self, path: &str) -> Option<Markdown> {
+        let mut markdown = match MarkdownProcessor::get()
+            .await
+            .process_file(&self.repo_path.join(path))
+            .await
+        {
+            Ok(markdown) => markdown,
+            Err(e) => {
+                warn!("Failed to process markdown file {}: {}", path, e);
+                return None;
+            }
+        };
+        match self.file_history(path).await {
+            Ok(Some((commit, first, last))) => markdown.set_source_commit(commit, first, last),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read git history of {}: {}", path, e),
+        }
+        Some(markdown)
+    }
+
+    /// 返回最后修改文件的提交，以及文件首次与最后一次提交的时间
+    async fn file_history(&self, path: &str) -> Result<Option<(String, DateTime<Local>, DateTime<Local>)>> {
+        let output = self.git(&["log", "--follow", "--format=%H %cI", "--", path]).await?;
+        let mut lines = output.lines();
+        let Some(latest) = lines.next() else {
+            return Ok(None);
+        };
+        let (commit, last) = parse_log_line(latest)?;
+        let first = match lines.last() {
+            Some(line) => parse_log_line(line)?.1,
+            None => last,
+        };
+        Ok(Some((commit, first, last)))
+    }
+
+    /// 读取文件在指定提交中的 slug，用于识别删除与改名前的文章
+    async fn slug_at(&self, commit: &str, path: &str) -> Option<String> {
+        let slug = match self.git(&["show", &format!("{commit}:{path}")]).await {
+            Ok(content) => Markdown::from_string_at(&content, Path::new(path), CONFIG.source_lang)
+                .map(|markdown| markdown.slug().to_owned())
+                .ok(),
+            Err(_) => None,
+        };
+        slug.or_else(|| file_stem(path))
+    }
+
+    fn content_dir(&self) -> &str {
+        self.config.path.as_deref().unwrap_or(".")
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        run_git(Some(&self.repo_path), args).await
+    }
+}
+
+async fn run_git(repo_path: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = tokio::process::Command::new("git");
+    if let Some(repo_path) = repo_path {
+        command.arg("-C").arg(repo_path);
+    }
+    let output = command
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim_end_matches('\n').to_owned())
+}
+
+fn parse_log_line(line: &str) -> Result<(String, DateTime<Local>)> {
+    let (commit, time) = line.split_once(' ').context("Invalid git log output")?;
+    let time = DateTime::parse_from_rfc3339(time).context("Invalid commit time")?;
+    Ok((commit.to_owned(), time.with_timezone(&Local)))
+}
+
+/// 仅同步非隐藏路径下的 markdown 文件
+fn is_content(path: &str) -> bool {
+    path.ends_with(".md") && !path.split('/').any(|c| c.starts_with('.'))
+}
+
+fn file_stem(path: &str) -> Option<String> {
+    Path::new(path).file_stem().and_then(|s| s.to_str()).map(str::to_owned)
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git_in(repo: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["-c", "user.name=suwen", "-c", "user.email=suwen@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap().trim().to_owned()
+    }
+
+    fn write_post(repo: &Path, name: &str, content: &str) {
+        let content = format!("---\ntype: article\ntitle: {name}\ntags: []\n---\n{content}\n");
+        std::fs::write(repo.join("posts").join(format!("{name}.md")), content).unwrap();
+    }
+
+    fn drain(rx: &mut mpsc::UnboundedReceiver<MarkdownChange>) -> Vec<MarkdownChange> {
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_sync_from_file_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let origin = dir.path().join("origin");
+        std::fs::create_dir_all(origin.join("posts")).unwrap();
+        git_in(&origin, &["init", "--quiet", "--initial-branch", "main"]);
+        write_post(&origin, "git-hello", "第一版");
+        write_post(&origin, "git-world", "正文");
+        git_in(&origin, &["add", "-A"]);
+        git_in(&origin, &["commit", "--quiet", "-m", "init"]);
+
+        let config = GitSourceConfig {
+            url: format!("file://{}", origin.display()),
+            branch: None,
+            path: Some("posts".to_owned()),
+            checkout_path: Some(dir.path().join("checkout").to_string_lossy().into_owned()),
+            poll_interval_secs: 1,
+        };
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sync = GitSync::new(config, tx);
+        sync.prepare().await.unwrap();
+        sync.sync().await.unwrap();
+        let head = git_in(&origin, &["rev-parse", "HEAD"]);
+        let changes = drain(&mut rx);
+        assert_eq!(changes.len(), 3);
+        for change in &changes[..2] {
+            assert!(matches!(change, MarkdownChange::Upsert(markdown) if markdown.source_commit() == Some(&head)));
+        }
+        assert!(matches!(&changes[2], MarkdownChange::SyncExisting(slugs) if slugs.len() == 2));
+
+        // 之后只按差异产生变更
+        write_post(&origin, "git-hello", "第二版");
+        std::fs::remove_file(origin.join("posts").join("git-world.md")).unwrap();
+        git_in(&origin, &["add", "-A"]);
+        git_in(&origin, &["commit", "--quiet", "-m", "update"]);
+        sync.sync().await.unwrap();
+        let head = git_in(&origin, &["rev-parse", "HEAD"]);
+        let changes = drain(&mut rx);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().any(|change| matches!(
+            change,
+            MarkdownChange::Upsert(markdown)
+                if markdown.slug() == "git-hello"
+                    && markdown.source_commit() == Some(&head)
+                    && markdown.content().contains("第二版")
+        )));
+        assert!(
+            changes
+                .iter()
+                .any(|change| matches!(change, MarkdownChange::Deleted(slug) if slug == "git-world"))
+        );
+
+        // HEAD 未变化时不产生变更
+        sync.sync().await.unwrap();
+        assert!(drain(&mut rx).is_empty());
+    }
+}
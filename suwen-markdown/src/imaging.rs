@@ -0,0 +1,284 @@
+use std::io::Cursor;
+
+use anyhow::{Result, anyhow, bail, ensure};
+use base64::prelude::*;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::webp::WebPDecoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+
+/// WebP 与 AVIF 的编码质量
+const QUALITY: u8 = 80;
+/// AVIF 编码速度，取值 1-10，越大越快但压缩率越低
+const AVIF_SPEED: u8 = 6;
+/// 占位图的宽度
+const PLACEHOLDER_WIDTH: u32 = 16;
+
+/// 解码后的图片，已按 EXIF 方向旋转；重新编码只写入像素，EXIF（包括 GPS 位置）等元数据不会保留
+pub(crate) struct DecodedImage(DynamicImage);
+
+impl DecodedImage {
+    pub(crate) fn decode(data: &[u8]) -> Result<Self> {
+        let reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+        // 动图只会解码出第一帧，保留原文件
+        if reader.format() == Some(ImageFormat::WebP) && WebPDecoder::new(Cursor::new(data))?.has_animation() {
+            bail!("Animated WebP is not supported");
+        }
+        let mut decoder = reader.into_decoder()?;
+        let orientation = decoder.orientation()?;
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+        Ok(Self(image))
+    }
+
+    pub(crate) fn width(&self) -> u32 {
+        self.0.width()
+    }
+
+    pub(crate) fn height(&self) -> u32 {
+        self.0.height()
+    }
+
+    /// 按比例缩放到指定宽度
+    pub(crate) fn resize(&self, width: u32) -> Self {
+        let height = (self.height() as u64 * width as u64 / self.width() as u64).max(1) as u32;
+        Self(self.0.resize_exact(width, height, FilterType::Lanczos3))
+    }
+
+    pub(crate) fn to_webp(&self) -> Result<Vec<u8>> {
+        let encoded = if self.0.color().has_alpha() {
+            let rgba = self.0.to_rgba8();
+            webp::Encoder::from_rgba(&rgba, self.width(), self.height()).encode_simple(false, QUALITY as f32)
+        } else {
+            let rgb = self.0.to_rgb8();
+            webp::Encoder::from_rgb(&rgb, self.width(), self.height()).encode_simple(false, QUALITY as f32)
+        };
+        Ok(encoded.map_err(|e| anyhow!("Failed to encode webp: {:?}", e))?.to_vec())
+    }
+
+    pub(crate) fn to_avif(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        let encoder = AvifEncoder::new_with_speed_quality(&mut buf, AVIF_SPEED, QUALITY);
+        if self.0.color().has_alpha() {
+            self.0.to_rgba8().write_with_encoder(encoder)?;
+        } else {
+            self.0.to_rgb8().write_with_encoder(encoder)?;
+        }
+        Ok(buf)
+    }
+
+    /// 生成用作占位图的极小 WebP，带透明通道的图片显示占位图会透出背景，因此不生成
+    pub(crate) fn placeholder(&self) -> Result<Option<String>> {
+        if self.0.color().has_alpha() {
+            return Ok(None);
+        }
+        let webp = self.resize(PLACEHOLDER_WIDTH.min(self.width())).to_webp()?;
+        Ok(Some(format!("data:image/webp;base64,{}", BASE64_STANDARD.encode(webp))))
+    }
+}
+
+/// 去除原样上传的图片中的 EXIF、XMP 等元数据，用于无法重新编码的图片（如动图）；ICC 色彩配置等其它信息保留
+pub(crate) fn strip_metadata(data: &[u8], ext: &str) -> Result<Vec<u8>> {
+    match ext {
+        "jpg" | "jpeg" => strip_jpeg(data),
+        "png" => strip_png(data),
+        "webp" => strip_webp(data),
+        // 手机拍摄的 HEIC 等格式同样带有位置信息，无法解码时也无法去除，拒绝上传
+        "heic" | "heif" | "tif" | "tiff" => bail!("Stripping metadata from {} is not supported", ext),
+        _ => Ok(data.to_vec()),
+    }
+}
+
+fn strip_jpeg(data: &[u8]) -> Result<Vec<u8>> {
+    ensure!(data.starts_with(&[0xff, 0xd8]), "Invalid JPEG header");
+    let mut stripped = data[..2].to_vec();
+    let mut pos = 2;
+    loop {
+        ensure!(pos + 1 < data.len() && data[pos] == 0xff, "Invalid JPEG marker");
+        let marker = data[pos + 1];
+        match marker {
+            // 填充字节
+            0xff => {
+                pos += 1;
+                continue;
+            }
+            // 扫描数据与之后的内容不含元数据，原样保留
+            0xda | 0xd9 => {
+                stripped.extend_from_slice(&data[pos..]);
+                return Ok(stripped);
+            }
+            _ => {}
+        }
+        ensure!(pos + 4 <= data.len(), "Truncated JPEG segment");
+        let end = pos + 2 + u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        ensure!(end >= pos + 4 && end <= data.len(), "Invalid JPEG segment length");
+        // APP1 存放 EXIF 与 XMP，APP13 存放包含 IPTC 的 Photoshop 信息
+        if !matches!(marker, 0xe1 | 0xed) {
+            stripped.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+}
+
+fn strip_png(data: &[u8]) -> Result<Vec<u8>> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    ensure!(data.starts_with(SIGNATURE), "Invalid PNG header");
+    let mut stripped = SIGNATURE.to_vec();
+    let mut pos = SIGNATURE.len();
+    while pos < data.len() {
+        ensure!(pos + 12 <= data.len(), "Truncated PNG chunk");
+        let len = u32::from_be_bytes(data[pos..pos + 4].try_into()?) as usize;
+        let end = pos + 12 + len;
+        ensure!(end <= data.len(), "Invalid PNG chunk length");
+        if !matches!(&data[pos + 4..pos + 8], b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") {
+            stripped.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+    Ok(stripped)
+}
+
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>> {
+    ensure!(
+        data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP",
+        "Invalid WebP header"
+    );
+    let mut stripped = data[..12].to_vec();
+    let mut pos = 12;
+    while pos < data.len() {
+        ensure!(pos + 8 <= data.len(), "Truncated WebP chunk");
+        let len = u32::from_le_bytes(data[pos + 4..pos + 8].try_into()?) as usize;
+        let end = (pos + 8 + len + len % 2).min(data.len());
+        ensure!(pos + 8 + len <= data.len(), "Invalid WebP chunk length");
+        match &data[pos..pos + 4] {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" if len > 0 => {
+                let flags = stripped.len() + 8;
+                stripped.extend_from_slice(&data[pos..end]);
+                // 同时清除扩展头中声明 EXIF 与 XMP 存在的标志位
+                stripped[flags] &= !0x0c;
+            }
+            _ => stripped.extend_from_slice(&data[pos..end]),
+        }
+        pos = end;
+    }
+    let riff_size = u32::try_from(stripped.len() - 8)?;
+    stripped[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::codecs::png::PngEncoder;
+    use image::{ImageEncoder, RgbImage};
+
+    use super::*;
+
+    /// 构造带 EXIF 的 JPEG：方向为顺时针旋转 90 度，并包含 GPS 信息
+    fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        let image = RgbImage::from_pixel(width, height, image::Rgb([200, 40, 40]));
+        JpegEncoder::new(&mut jpeg)
+            .write_image(&image, width, height, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        let mut tiff = b"MM\0\x2a\0\0\0\x08".to_vec();
+        tiff.extend([0, 2]);
+        tiff.extend([0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0]);
+        tiff.extend([0x88, 0x25, 0, 4, 0, 0, 0, 1, 0, 0, 0, 38]);
+        tiff.extend([0, 0, 0, 0]);
+        tiff.extend([0, 1]);
+        tiff.extend([0x00, 0x01, 0, 2, 0, 0, 0, 2, b'N', 0, 0, 0]);
+        tiff.extend([0, 0, 0, 0]);
+        let mut app1 = vec![0xff, 0xe1];
+        app1.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        app1.extend(b"Exif\0\0");
+        app1.extend(tiff);
+        jpeg.splice(2..2, app1);
+        jpeg
+    }
+
+    #[test]
+    fn test_orientation_applied_and_metadata_stripped() {
+        let image = DecodedImage::decode(&jpeg_with_exif(40, 20)).unwrap();
+        assert_eq!((image.width(), image.height()), (20, 40));
+        let webp = image.resize(10).to_webp().unwrap();
+        assert_eq!(
+            imagesize::blob_size(&webp).map(|s| (s.width, s.height)).unwrap(),
+            (10, 20)
+        );
+        assert!(!webp.windows(4).any(|w| w == b"Exif"));
+    }
+
+    #[test]
+    fn test_strip_jpeg_metadata() {
+        let jpeg = jpeg_with_exif(40, 20);
+        let stripped = strip_metadata(&jpeg, "jpg").unwrap();
+        assert!(!stripped.windows(4).any(|w| w == b"Exif"));
+        assert!(stripped.len() < jpeg.len());
+        // 去除 EXIF 后方向信息随之丢失，但图片仍然可以正常解码
+        let image = DecodedImage::decode(&stripped).unwrap();
+        assert_eq!((image.width(), image.height()), (40, 20));
+    }
+
+    #[test]
+    fn test_strip_png_metadata() {
+        let mut png = Vec::new();
+        DynamicImage::new_rgb8(4, 4)
+            .write_with_encoder(PngEncoder::new(&mut png))
+            .unwrap();
+        // 在 IHDR 之后插入 eXIf 与 tEXt 块
+        let ihdr_end = 8 + 12 + 13;
+        for (kind, payload) in [(b"eXIf", b"MM\0\x2a".as_slice()), (b"tEXt", b"GPS\0N".as_slice())] {
+            let mut chunk = (payload.len() as u32).to_be_bytes().to_vec();
+            chunk.extend(kind);
+            chunk.extend(payload);
+            chunk.extend([0; 4]);
+            png.splice(ihdr_end..ihdr_end, chunk);
+        }
+        let stripped = strip_metadata(&png, "png").unwrap();
+        assert!(!stripped.windows(4).any(|w| w == b"eXIf" || w == b"tEXt"));
+        assert_eq!(DecodedImage::decode(&stripped).unwrap().width(), 4);
+    }
+
+    #[test]
+    fn test_strip_webp_metadata() {
+        let chunk = |kind: &[u8], payload: &[u8]| {
+            let mut chunk = kind.to_vec();
+            chunk.extend((payload.len() as u32).to_le_bytes());
+            chunk.extend(payload);
+            if payload.len() % 2 == 1 {
+                chunk.push(0);
+            }
+            chunk
+        };
+        let body = [
+            chunk(b"VP8X", &[0x0c | 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            chunk(b"ANIM", &[0; 6]),
+            chunk(b"EXIF", b"MM\0\x2a\0"),
+            chunk(b"XMP ", b"<x/>"),
+        ]
+        .concat();
+        let mut webp = b"RIFF".to_vec();
+        webp.extend((body.len() as u32 + 4).to_le_bytes());
+        webp.extend(b"WEBP");
+        webp.extend(body);
+
+        let stripped = strip_metadata(&webp, "webp").unwrap();
+        let expected = [
+            b"RIFF".as_slice(),
+            &(4 + 18 + 14u32).to_le_bytes(),
+            b"WEBP",
+            &chunk(b"VP8X", &[0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            &chunk(b"ANIM", &[0; 6]),
+        ]
+        .concat();
+        assert_eq!(stripped, expected);
+    }
+
+    #[test]
+    fn test_strip_unsupported_metadata() {
+        assert!(strip_metadata(b"\0\0\0\x18ftypheic", "heic").is_err());
+        assert_eq!(strip_metadata(b"GIF89a", "gif").unwrap(), b"GIF89a");
+    }
+}
@@ -1,14 +1,22 @@
+mod download;
+mod ssg;
+mod wordpress;
 mod xlog;
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use futures::TryStreamExt;
 use futures::stream::FuturesUnordered;
+use lol_html::{HtmlRewriter, Settings, element};
 use tokio::fs::{self, create_dir_all};
 use tokio::task::JoinSet;
 
 use crate::Markdown;
+pub use crate::importer::ssg::import_file as SsgImporter;
+pub use crate::importer::wordpress::{COMMENTS_FILE, ImportedComment, import_file as import_wordpress};
 pub use crate::importer::xlog::import_file as XlogImporter;
 
 pub async fn import_path<T, F>(source: PathBuf, output: PathBuf, obj_output: Option<PathBuf>, importer: T) -> Result<()>
@@ -21,13 +29,46 @@ where
     create_dir_all(&obj_output).await?;
     let mut join_set = JoinSet::new();
     for file in collect_files(source).await? {
-        join_set.spawn(importer(file, output.clone(), obj_output.clone()));
+        let task = importer(file.clone(), output.clone(), obj_output.clone());
+        join_set.spawn(async move { (file, task.await) });
     }
-    let write_task = join_set
-        .join_all()
-        .await
+    let mut results = join_set.join_all().await;
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut markdowns = Vec::new();
+    for (file, result) in results {
+        match result {
+            Ok(markdown) => markdowns.push((file, markdown)),
+            Err(e) => warn!("Skipped {}: {:#}", file.display(), e),
+        }
+    }
+    write_markdowns(&output, unique_slugs(markdowns)).await
+}
+
+/// 多个文件得到相同的 slug 时只保留路径排序靠前的文件，避免写入时互相覆盖
+fn unique_slugs(markdowns: Vec<(PathBuf, Markdown)>) -> Vec<Markdown> {
+    let mut sources = HashMap::<String, PathBuf>::new();
+    let mut unique = Vec::new();
+    for (file, markdown) in markdowns {
+        match sources.entry(markdown.slug().to_owned()) {
+            Entry::Occupied(entry) => warn!(
+                "Skipped {}: slug {} is already used by {}",
+                file.display(),
+                markdown.slug(),
+                entry.get().display()
+            ),
+            Entry::Vacant(entry) => {
+                entry.insert(file);
+                unique.push(markdown);
+            }
+        }
+    }
+    unique
+}
+
+/// 以 slug 作为文件名写入输出目录
+async fn write_markdowns(output: &Path, markdowns: impl IntoIterator<Item = Markdown>) -> Result<()> {
+    let write_task = markdowns
         .into_iter()
-        .filter_map(Result::ok)
         .filter_map(|result| {
             let target = output.join(format!("{}.md", result.slug()));
             if let Ok(str) = result.to_string() {
@@ -40,6 +81,52 @@ where
     Ok(write_task.try_collect().await?)
 }
 
+/// 引用媒体的元素，视频的封面同样需要下载或复制
+const MEDIA_ELEMENTS: &str = "img[src], video[src], video[poster], audio[src], source[src]";
+
+/// 收集 html 中图片、视频与音频引用的地址
+fn collect_sources(html: &str) -> Result<Vec<String>> {
+    let mut sources = Vec::new();
+    let mut scanner = HtmlRewriter::new(
+        Settings {
+            element_content_handlers: vec![element!(MEDIA_ELEMENTS, |el| {
+                sources.extend(el.get_attribute("src"));
+                sources.extend(el.get_attribute("poster"));
+                Ok(())
+            })],
+            ..Settings::new()
+        },
+        |_: &[u8]| {},
+    );
+    scanner.write(html.as_bytes())?;
+    scanner.end()?;
+    Ok(sources)
+}
+
+/// 替换为下载或复制后的地址，响应式图片属性仍指向原站点的文件，一并去掉
+fn replace_sources(html: &str, url_map: &HashMap<String, String>) -> Result<String> {
+    let mut buf = Vec::new();
+    let mut rewriter = HtmlRewriter::new(
+        Settings {
+            element_content_handlers: vec![element!(MEDIA_ELEMENTS, |el| {
+                for name in ["src", "poster"] {
+                    if let Some(new_url) = el.get_attribute(name).and_then(|url| url_map.get(&url)) {
+                        el.set_attribute(name, new_url)?;
+                    }
+                }
+                el.remove_attribute("srcset");
+                el.remove_attribute("sizes");
+                Ok(())
+            })],
+            ..Settings::new()
+        },
+        |c: &[u8]| buf.extend_from_slice(c),
+    );
+    rewriter.write(html.as_bytes())?;
+    rewriter.end()?;
+    Ok(String::from_utf8(buf)?)
+}
+
 async fn collect_files(source: PathBuf) -> Result<Vec<PathBuf>> {
     let (mut dirs, mut files) = (vec![source], Vec::new());
     while let Some(dir) = dirs.pop() {
@@ -58,12 +145,50 @@ async fn collect_files(source: PathBuf) -> Result<Vec<PathBuf>> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::fs::read_to_string;
     use std::path::PathBuf;
 
-    use crate::importer::{XlogImporter, import_path};
+    use suwen_config::Lang;
+
+    use crate::importer::{Markdown, XlogImporter, collect_sources, import_path, replace_sources, unique_slugs};
     use crate::parse_markdown;
 
+    #[test]
+    fn test_rewrite_sources() {
+        let html = r#"<img src="/a.png" srcset="/a-300.png 300w" sizes="100vw"><video poster="/p.jpg"><source src="/v.mp4"></video><a href="/a.png">a</a>"#;
+        assert_eq!(collect_sources(html).unwrap(), ["/a.png", "/p.jpg", "/v.mp4"]);
+        let url_map = HashMap::from([
+            ("/a.png".to_owned(), "objects/a.png".to_owned()),
+            ("/p.jpg".to_owned(), "objects/p.jpg".to_owned()),
+        ]);
+        assert_eq!(
+            replace_sources(html, &url_map).unwrap(),
+            r#"<img src="objects/a.png"><video poster="objects/p.jpg"><source src="/v.mp4"></video><a href="/a.png">a</a>"#
+        );
+    }
+
+    #[test]
+    fn test_unique_slugs() {
+        let markdown = |slug: &str, title: &str| {
+            let content = format!("---\ntype: article\nslug: {slug}\ntitle: {title}\ntags: []\n---\nbody\n");
+            Markdown::from_string(&content, Lang::default()).unwrap()
+        };
+        let unique = unique_slugs(vec![
+            (PathBuf::from("a/first.md"), markdown("same", "first")),
+            (PathBuf::from("b.md"), markdown("other", "other")),
+            (PathBuf::from("c/first.md"), markdown("same", "second")),
+        ]);
+        let titles = unique
+            .iter()
+            .map(|markdown| match markdown {
+                Markdown::Article { title, .. } => title.as_str(),
+                Markdown::Short { .. } => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["first", "other"]);
+    }
+
     #[ignore = "only for manual test"]
     #[tokio::test]
     async fn test_format_path() {
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use itertools::Itertools;
+use serde_json::{Map, Value};
+
+/// Hugo、Jekyll、Hexo 的 front matter，统一转换为 JSON 对象后按字段读取
+pub(super) struct FrontMatter(Map<String, Value>);
+
+impl FrontMatter {
+    /// 拆分 front matter 与正文，支持 --- 包裹的 YAML 与 +++ 包裹的 TOML；
+    /// Hexo 允许省略开头的 ---，此时分隔线之前能解析为 YAML 对象的部分视为 front matter
+    pub(super) fn split(input: &str) -> Result<(Self, &str)> {
+        let input = input.trim_start_matches('\u{feff}');
+        if let Some((front_matter, body)) = fenced(input, "+++") {
+            let table: toml::Table = toml::from_str(front_matter).context("Failed to parse TOML front matter")?;
+            return Ok((
+                Self(
+                    toml_to_json(toml::Value::Table(table))
+                        .as_object()
+                        .cloned()
+                        .unwrap_or_default(),
+                ),
+                body,
+            ));
+        }
+        if let Some((front_matter, body)) = fenced(input, "---") {
+            return Ok((Self::from_yaml(front_matter)?, body));
+        }
+        if let Some((front_matter, body)) = input.split_once("\n---\n")
+            && let Ok(front_matter) = Self::from_yaml(front_matter)
+        {
+            return Ok((front_matter, body));
+        }
+        bail!("Front matter not found")
+    }
+
+    fn from_yaml(input: &str) -> Result<Self> {
+        match serde_yaml::from_str::<Value>(input).context("Failed to parse YAML front matter")? {
+            Value::Object(map) => Ok(Self(map)),
+            Value::Null => Ok(Self(Map::new())),
+            _ => bail!("Front matter is not a mapping"),
+        }
+    }
+
+    /// 按顺序读取第一个存在的字符串字段
+    pub(super) fn string(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|key| match self.0.get(*key)? {
+            Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_owned()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+    }
+
+    /// 读取字符串或字符串列表，嵌套的列表（如 Hexo 的多级分类）会被展开
+    pub(super) fn strings(&self, key: &str) -> Vec<String> {
+        fn collect(value: &Value, result: &mut Vec<String>) {
+            match value {
+                Value::String(s) => {
+                    result.extend(s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_owned))
+                }
+                Value::Number(n) => result.push(n.to_string()),
+                Value::Array(values) => values.iter().for_each(|v| collect(v, result)),
+                _ => {}
+            }
+        }
+        let mut result = Vec::new();
+        if let Some(value) = self.0.get(key) {
+            collect(value, &mut result);
+        }
+        result
+    }
+
+    pub(super) fn bool(&self, key: &str) -> Option<bool> {
+        match self.0.get(key)? {
+            Value::Bool(b) => Some(*b),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub(super) fn date(&self, keys: &[&str]) -> Option<DateTime<Local>> {
+        keys.iter().find_map(|key| parse_date(self.string(&[key])?.as_str()))
+    }
+
+    /// 封面图可能是字符串、列表或 { image: ... } 形式的对象
+    pub(super) fn cover(&self) -> Option<String> {
+        [
+            "cover",
+            "image",
+            "images",
+            "featured_image",
+            "cover_image",
+            "thumbnail",
+            "banner",
+        ]
+        .iter()
+        .find_map(|key| match self.0.get(*key)? {
+            Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_owned()),
+            Value::Array(values) => values.iter().find_map(|v| v.as_str()).map(str::to_owned),
+            Value::Object(map) => ["image", "src", "path"]
+                .iter()
+                .find_map(|key| map.get(*key)?.as_str())
+                .map(str::to_owned),
+            _ => None,
+        })
+    }
+}
+
+fn fenced<'a>(input: &'a str, fence: &str) -> Option<(&'a str, &'a str)> {
+    let rest = input.strip_prefix(fence)?;
+    let rest = rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n'))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == fence {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(values) => Value::Array(values.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect()),
+    }
+}
+
+/// 解析常见的日期写法，未带时区的按本地时间处理
+pub(super) fn parse_date(input: &str) -> Option<DateTime<Local>> {
+    let input = input.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Some(date.with_timezone(&Local));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S %z", "%Y-%m-%d %H:%M %z", "%Y-%m-%dT%H:%M:%S%.f%z"] {
+        if let Ok(date) = DateTime::parse_from_str(input, format) {
+            return Some(date.with_timezone(&Local));
+        }
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        .or_else(|| {
+            ["%Y-%m-%d", "%Y/%m/%d"]
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(input, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// 将 Hugo 的 aliases 或 Jekyll 的 redirect_from 中的路径转换为 slug
+pub(super) fn alias_slug(alias: &str) -> Option<String> {
+    let path = alias.split(['?', '#']).next()?;
+    let segment = path.split('/').filter(|s| !s.is_empty()).collect_vec().pop()?;
+    let segment = segment
+        .strip_suffix(".html")
+        .or_else(|| segment.strip_suffix(".htm"))
+        .unwrap_or(segment);
+    (!segment.is_empty() && segment != "index").then(|| segment.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_toml() {
+        let (toml, body) =
+            FrontMatter::split("+++\ntitle = \"a\"\ndate = 2021-05-06\ntags = [\"x\"]\n+++\nbody").unwrap();
+        assert_eq!(toml.string(&["title"]).as_deref(), Some("a"));
+        assert_eq!(toml.strings("tags"), ["x"]);
+        assert!(toml.date(&["date"]).is_some());
+        assert_eq!(body, "body");
+    }
+
+    #[test]
+    fn test_split_yaml() {
+        // 正文中的分隔线不属于 front matter
+        let (yaml, body) = FrontMatter::split("---\ntitle: b\ndraft: true\n---\n---\nbody").unwrap();
+        assert_eq!(yaml.bool("draft"), Some(true));
+        assert_eq!(body, "---\nbody");
+    }
+
+    #[test]
+    fn test_split_hexo() {
+        // Hexo 允许省略开头的分隔线
+        let (hexo, body) = FrontMatter::split("title: c\ncategories:\n- [a, b]\n---\nbody").unwrap();
+        assert_eq!(hexo.strings("categories"), ["a", "b"]);
+        assert_eq!(body, "body");
+    }
+
+    #[test]
+    fn test_split_without_front_matter() {
+        assert!(FrontMatter::split("just text\n\n---\n\nmore").is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert!(parse_date("2019-01-02 03:04:05 +0800").is_some());
+    }
+
+    #[test]
+    fn test_alias_slug() {
+        assert_eq!(alias_slug("/posts/old-name/").as_deref(), Some("old-name"));
+        assert_eq!(alias_slug("/2021/legacy.html").as_deref(), Some("legacy"));
+    }
+}
@@ -0,0 +1,316 @@
+mod front_matter;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use pathdiff::diff_paths;
+use pulldown_cmark::{Event, Tag};
+use pulldown_cmark_to_cmark::cmark_resume;
+use regex::{Captures, Regex};
+use suwen_config::CONFIG;
+
+use crate::importer::ssg::front_matter::{FrontMatter, alias_slug, parse_date};
+use crate::importer::{Markdown, collect_sources, replace_sources};
+use crate::parse_markdown;
+
+/// 导入 Hugo、Jekyll、Hexo 的文章，front matter 的格式按文件内容识别
+pub async fn import_file(file: PathBuf, output: PathBuf, obj_output: PathBuf) -> Result<Markdown> {
+    if file.extension().is_none_or(|ext| ext != "md" && ext != "markdown") {
+        bail!("Unsupported file type: {}", file.display());
+    }
+    // Hugo 的 _index.md 是栏目的列表页，不是文章
+    if file.file_stem().is_some_and(|stem| stem == "_index") {
+        bail!("Skipping section page: {}", file.display());
+    }
+    let input = tokio::fs::read_to_string(&file).await?;
+    let (front_matter, body) =
+        FrontMatter::split(&input).with_context(|| format!("Failed to read front matter: {}", file.display()))?;
+    let (file_date, file_slug) = slug_from_path(&file)?;
+    let slug = front_matter.string(&["slug"]).unwrap_or(file_slug);
+    let created_at = front_matter.date(&["date"]).or(file_date);
+    let published_at = front_matter.date(&["publishDate", "publish_date"]).or(created_at);
+    let updated_at = front_matter
+        .date(&["lastmod", "updated", "last_modified_at", "modified"])
+        .or(created_at);
+    let draft = front_matter.bool("draft").unwrap_or(false)
+        || front_matter.bool("published") == Some(false)
+        || file.components().any(|c| c.as_os_str() == "_drafts");
+    // suwen 没有分类，分类与标签合并
+    let tags = front_matter
+        .strings("tags")
+        .into_iter()
+        .chain(front_matter.strings("categories"))
+        .unique()
+        .collect();
+    let aliases = ["aliases", "redirect_from"]
+        .iter()
+        .flat_map(|key| front_matter.strings(key))
+        .filter_map(|alias| alias_slug(&alias))
+        .filter(|alias| *alias != slug)
+        .unique()
+        .collect();
+    let mut assets = Assets {
+        file: &file,
+        slug: &slug,
+        output: &output,
+        obj_output: &obj_output,
+        copied: HashMap::new(),
+    };
+    let content = convert_body(body, &mut assets).await?;
+    let cover_images = match front_matter.cover() {
+        Some(cover) => Some(vec![assets.resolve(&cover).await]),
+        None => None,
+    };
+    Ok(Markdown::Article {
+        title: front_matter.string(&["title"]).unwrap_or_else(|| slug.clone()),
+        slug,
+        tags,
+        cover_images,
+        content,
+        publish: draft.then_some(false),
+        created_at,
+        updated_at,
+        published_at,
+        series: None,
+        aliases,
+        lang: CONFIG.source_lang,
+        source_commit: None,
+        media: Vec::new(),
+    })
+}
+
+/// 从文件路径推断 slug：Hugo 的页面包取目录名，Jekyll 的文件名去掉开头的日期并作为发布时间
+fn slug_from_path(file: &Path) -> Result<(Option<DateTime<Local>>, String)> {
+    static JEKYLL_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d{4}-\d{2}-\d{2})-(.+)$").unwrap());
+    let stem = file.file_stem().and_then(|s| s.to_str()).context("Invalid file name")?;
+    let stem = if stem == "index" {
+        file.parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|s| s.to_str())
+            .context("Invalid page bundle directory")?
+    } else {
+        stem
+    };
+    Ok(match JEKYLL_NAME.captures(stem) {
+        Some(caps) => (parse_date(&caps[1]), caps[2].to_owned()),
+        None => (None, stem.to_owned()),
+    })
+}
+
+/// 将常见的图片短代码转换为 markdown 图片，并复制正文中引用的本地文件
+async fn convert_body(body: &str, assets: &mut Assets<'_>) -> Result<String> {
+    let body = replace_shortcodes(body);
+    let events = parse_markdown(&body)?;
+    let mut converted = Vec::with_capacity(events.len());
+    for mut event in events {
+        match &mut event {
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                *dest_url = assets.resolve(dest_url).await.into();
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                *html = rewrite_html(html, assets).await?.into();
+            }
+            _ => {}
+        }
+        converted.push(event);
+    }
+    let mut buf = String::new();
+    cmark_resume(converted.into_iter(), &mut buf, None).context("Failed to resume cmark")?;
+    Ok(buf)
+}
+
+fn replace_shortcodes(body: &str) -> String {
+    // Hugo: {{< figure src="/img/a.png" alt="..." >}}
+    static HUGO_FIGURE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\{\{[<%]\s*figure\s+(.*?)\s*/?\s*[>%]\}\}").unwrap());
+    static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(\w+)\s*=\s*"([^"]*)""#).unwrap());
+    // Hexo: {% asset_img a.png 标题 %}
+    static HEXO_ASSET_IMG: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"\{%\s*asset_img\s+(\S+)(?:\s+(.*?))?\s*%\}"#).unwrap());
+    // Jekyll: {{ site.baseurl }}/img/a.png 与 {{ "/img/a.png" | relative_url }}
+    static JEKYLL_BASEURL: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\{\{\s*site\.(?:baseurl|url)\s*\}\}").unwrap());
+    static JEKYLL_URL_FILTER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"\{\{\s*["']([^"']+)["']\s*\|\s*(?:relative_url|absolute_url)\s*\}\}"#).unwrap());
+    let body = HUGO_FIGURE.replace_all(body, |caps: &Captures| {
+        let attrs = ATTRIBUTE
+            .captures_iter(&caps[1])
+            .map(|c| (c[1].to_owned(), c[2].to_owned()))
+            .collect::<HashMap<_, _>>();
+        let alt = ["alt", "title", "caption"]
+            .iter()
+            .find_map(|key| attrs.get(*key))
+            .map(String::as_str)
+            .unwrap_or_default();
+        match attrs.get("src") {
+            Some(src) => format!("![{}]({})", alt, src),
+            None => caps[0].to_owned(),
+        }
+    });
+    let body = HEXO_ASSET_IMG.replace_all(&body, |caps: &Captures| {
+        let title = caps.get(2).map_or("", |m| m.as_str().trim_matches(['"', '\'']));
+        format!("![{}]({})", title, &caps[1])
+    });
+    let body = JEKYLL_BASEURL.replace_all(&body, "");
+    JEKYLL_URL_FILTER.replace_all(&body, "$1").into_owned()
+}
+
+/// 替换 html 片段中图片与视频引用的本地文件
+async fn rewrite_html(html: &str, assets: &mut Assets<'_>) -> Result<String> {
+    let sources = collect_sources(html)?;
+    if sources.is_empty() {
+        return Ok(html.to_owned());
+    }
+    let mut url_map = HashMap::new();
+    for src in sources {
+        let resolved = assets.resolve(&src).await;
+        url_map.insert(src, resolved);
+    }
+    replace_sources(html, &url_map)
+}
+
+/// 文章引用的静态文件，复制到 obj_output 后以相对输出目录的路径引用
+struct Assets<'a> {
+    file: &'a Path,
+    slug: &'a str,
+    output: &'a Path,
+    obj_output: &'a Path,
+    copied: HashMap<String, String>,
+}
+
+impl Assets<'_> {
+    /// 远程地址保持不变，找不到的本地文件添加死链标记
+    async fn resolve(&mut self, url: &str) -> String {
+        if url.starts_with("//") {
+            return format!("https:{}", url);
+        }
+        if url.is_empty()
+            || url.starts_with("$dead_link")
+            || ["http://", "https://", "data:", "mailto:", "#"]
+                .iter()
+                .any(|prefix| url.starts_with(prefix))
+        {
+            return url.to_owned();
+        }
+        if let Some(resolved) = self.copied.get(url) {
+            return resolved.clone();
+        }
+        let resolved = match self.copy(url).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                error!(
+                    "Failed to copy asset {} referenced by {}: {}",
+                    url,
+                    self.file.display(),
+                    e
+                );
+                format!("$dead_link/{}", url)
+            }
+        };
+        self.copied.insert(url.to_owned(), resolved.clone());
+        resolved
+    }
+
+    async fn copy(&self, url: &str) -> Result<String> {
+        let source = self.find(url).context("File not found")?;
+        let ext = source.extension().and_then(|ext| ext.to_str()).unwrap_or("bin");
+        let target = self.obj_output.join(format!(
+            "{}-asset-{}.{}",
+            self.slug,
+            self.copied.len(),
+            ext.to_lowercase()
+        ));
+        tokio::fs::copy(&source, &target).await?;
+        Ok(diff_paths(&target, self.output)
+            .context("Failed to compute relative path")?
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    /// 以 / 开头的路径依次在上级目录的 static（Hugo）、source（Hexo）与站点根目录（Jekyll）中查找，
+    /// 相对路径在文章所在目录与同名的资源目录（Hexo 的 post_asset_folder）中查找
+    fn find(&self, url: &str) -> Option<PathBuf> {
+        let path = url.split(['?', '#']).next()?.replace("%20", " ");
+        let candidates = match path.strip_prefix('/') {
+            Some(path) => self
+                .file
+                .ancestors()
+                .skip(1)
+                .flat_map(|dir| {
+                    [
+                        dir.join("static").join(path),
+                        dir.join("source").join(path),
+                        dir.join(path),
+                    ]
+                })
+                .collect_vec(),
+            None => {
+                let dir = self.file.parent()?;
+                let mut candidates = vec![dir.join(&path)];
+                if let Some(stem) = self.file.file_stem() {
+                    candidates.push(dir.join(stem).join(&path));
+                }
+                candidates
+            }
+        };
+        candidates.into_iter().find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    fn find(file: &Path, url: &str) -> Option<PathBuf> {
+        let assets = Assets {
+            file,
+            slug: "slug",
+            output: Path::new("/output"),
+            obj_output: Path::new("/output/objects"),
+            copied: HashMap::new(),
+        };
+        assets.find(url)
+    }
+
+    #[test]
+    fn test_find_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        // Hugo：以 / 开头的路径位于站点的 static 目录
+        let hugo_post = root.join("hugo/content/posts/a.md");
+        let hugo_image = root.join("hugo/static/images/a.png");
+        touch(&hugo_post);
+        touch(&hugo_image);
+        assert_eq!(find(&hugo_post, "/images/a.png?v=1"), Some(hugo_image));
+
+        // Hugo page bundle：资源与 index.md 位于同一目录
+        let bundle = root.join("hugo/content/posts/bundle/index.md");
+        let cover = root.join("hugo/content/posts/bundle/my cover.jpg");
+        touch(&bundle);
+        touch(&cover);
+        assert_eq!(find(&bundle, "my%20cover.jpg#top"), Some(cover));
+
+        // Hexo：以 / 开头的路径位于 source 目录，相对路径可以位于同名的资源目录
+        let hexo_post = root.join("hexo/source/_posts/b.md");
+        let hexo_image = root.join("hexo/source/images/b.png");
+        let asset = root.join("hexo/source/_posts/b/diagram.svg");
+        touch(&hexo_post);
+        touch(&hexo_image);
+        touch(&asset);
+        assert_eq!(find(&hexo_post, "/images/b.png"), Some(hexo_image));
+        assert_eq!(find(&hexo_post, "diagram.svg"), Some(asset));
+
+        assert_eq!(find(&hexo_post, "/images/missing.png"), None);
+        assert_eq!(find(&hexo_post, "missing.png"), None);
+    }
+}
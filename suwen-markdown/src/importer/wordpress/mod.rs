@@ -0,0 +1,447 @@
+mod schema;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use futures::{StreamExt, stream};
+use itertools::Itertools;
+use percent_encoding::percent_decode_str;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use suwen_config::CONFIG;
+use tokio::fs::{create_dir_all, read_to_string};
+
+use crate::importer::download::batch_download_replace;
+use crate::importer::wordpress::schema::{Item, Rss};
+use crate::importer::{Markdown, collect_sources, replace_sources, write_markdowns};
+
+/// 导入的评论保存在输出目录中的文件，文章同步后再导入数据库
+pub const COMMENTS_FILE: &str = "comments.json";
+
+/// 从 WordPress 导入的评论
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedComment {
+    /// 评论在原站点的唯一标识，重复导入时据此跳过
+    pub source_id: String,
+    /// 所回复的最上层评论的 source_id，suwen 的评论只有两层
+    pub parent_source_id: Option<String>,
+    pub slug: String,
+    /// 评论者的昵称，为空时作为匿名用户导入
+    pub author: Option<String>,
+    pub content: String,
+    pub created_at: DateTime<Local>,
+}
+
+/// 导入 WordPress 导出的 WXR 文件，文章写入 output，已审核通过的评论写入 output 中的 comments.json，返回评论数
+pub async fn import_file(source: PathBuf, output: PathBuf, obj_output: Option<PathBuf>) -> Result<usize> {
+    // 摘要 excerpt:encoded 与正文 content:encoded 的本地名相同，解析前改名以免冲突
+    static EXCERPT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<(/?)excerpt:encoded\b").unwrap());
+    let xml = read_to_string(&source).await?;
+    let xml = EXCERPT.replace_all(&xml, "<${1}excerpt:excerpt");
+    let rss: Rss = quick_xml::de::from_str(&xml).context("Failed to parse WXR file")?;
+    create_dir_all(&output).await?;
+    let obj_output = obj_output.unwrap_or_else(|| output.join("objects"));
+    create_dir_all(&obj_output).await?;
+    let site = rss.channel.base_site_url.trim_end_matches('/').to_owned();
+    let (attachments, posts): (Vec<_>, Vec<_>) = rss
+        .channel
+        .items
+        .into_iter()
+        .filter(|item| item.post_type == "attachment" || item.post_type == "post")
+        .partition(|item| item.post_type == "attachment");
+    let attachments = attachments
+        .into_iter()
+        .filter_map(|item| Some((item.post_id, item.attachment_url?)))
+        .collect::<HashMap<_, _>>();
+    let results = stream::iter(posts)
+        .map(|item| handle_post(item, &site, &attachments, &output, &obj_output))
+        .buffer_unordered(4)
+        .collect::<Vec<_>>()
+        .await;
+    let (mut markdowns, mut comments) = (Vec::new(), Vec::new());
+    for result in results {
+        match result {
+            Ok(Some((markdown, post_comments))) => {
+                markdowns.push(markdown);
+                comments.extend(post_comments);
+            }
+            Ok(None) => {}
+            Err(e) => error!("Failed to import WordPress post: {:#}", e),
+        }
+    }
+    write_markdowns(&output, markdowns).await?;
+    tokio::fs::write(output.join(COMMENTS_FILE), serde_json::to_vec_pretty(&comments)?).await?;
+    Ok(comments.len())
+}
+
+/// 回收站与自动草稿中的文章会被跳过
+async fn handle_post(
+    item: Item,
+    site: &str,
+    attachments: &HashMap<u64, String>,
+    output: &Path,
+    obj_output: &Path,
+) -> Result<Option<(Markdown, Vec<ImportedComment>)>> {
+    let publish = match item.status.as_str() {
+        "publish" | "future" => None,
+        "draft" | "pending" | "private" => Some(false),
+        _ => return Ok(None),
+    };
+    let slug = match percent_decode_str(item.post_name.trim()).decode_utf8_lossy() {
+        name if name.is_empty() => format!("post-{}", item.post_id),
+        name => name.into_owned(),
+    };
+    let html = prepare_html(&item.encoded);
+    let cover = item
+        .meta
+        .iter()
+        .find(|meta| meta.meta_key == "_thumbnail_id")
+        .and_then(|meta| meta.meta_value.trim().parse().ok())
+        .and_then(|id| attachments.get(&id));
+    let sources = collect_sources(&html)?
+        .into_iter()
+        .chain(cover.cloned())
+        .unique()
+        .filter_map(|src| Some((absolute_url(site, &src)?, src)))
+        .collect::<HashMap<_, _>>();
+    let results = batch_download_replace(
+        sources
+            .keys()
+            .enumerate()
+            .map(|(idx, url)| (url, obj_output.join(format!("{}-attachment-{}", slug, idx)))),
+        output,
+    )
+    .await;
+    let url_map = results
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|(url, new_url)| Some((sources.get(url)?.clone(), new_url)))
+        .collect::<HashMap<_, _>>();
+    let content = html_to_markdown(&replace_sources(&html, &url_map)?)?;
+    let created_at = parse_date(&item.post_date_gmt, &item.post_date);
+    let updated_at = parse_date(&item.post_modified_gmt, &item.post_modified).or(created_at);
+    let comments = collect_comments(site, &slug, item.comments);
+    let tags = item
+        .categories
+        .into_iter()
+        .filter(|category| {
+            (category.domain == "post_tag" || category.domain == "category") && category.nicename != "uncategorized"
+        })
+        .map(|category| decode_entities(category.name.trim()))
+        .filter(|name| !name.is_empty())
+        .unique()
+        .collect();
+    Ok(Some((
+        Markdown::Article {
+            title: match decode_entities(item.title.trim()) {
+                title if title.is_empty() => slug.clone(),
+                title => title,
+            },
+            cover_images: cover.map(|cover| vec![url_map.get(cover).cloned().unwrap_or_else(|| cover.clone())]),
+            slug,
+            tags,
+            content,
+            publish,
+            created_at,
+            updated_at,
+            published_at: created_at,
+            series: None,
+            aliases: Vec::new(),
+            lang: CONFIG.source_lang,
+            source_commit: None,
+            media: Vec::new(),
+        },
+        comments,
+    )))
+}
+
+/// 只导入审核通过的普通评论，多层回复挂到最上层的已审核评论下
+fn collect_comments(site: &str, slug: &str, comments: Vec<schema::Comment>) -> Vec<ImportedComment> {
+    let parents = comments
+        .iter()
+        .map(|comment| (comment.comment_id, comment.comment_parent))
+        .collect::<HashMap<_, _>>();
+    let comments = comments
+        .into_iter()
+        .filter(|comment| comment.comment_approved == "1" && matches!(comment.comment_type.as_str(), "" | "comment"))
+        .collect_vec();
+    let approved = comments
+        .iter()
+        .map(|comment| comment.comment_id)
+        .collect::<HashSet<_>>();
+    let source_id = |id: u64| format!("wordpress:{}:{}", site, id);
+    comments
+        .into_iter()
+        .filter_map(|comment| {
+            let mut root = None;
+            let mut parent = comment.comment_parent;
+            let mut visited = HashSet::from([comment.comment_id]);
+            while parent != 0 {
+                // 导出文件中循环引用的评论视为最上层评论
+                if !visited.insert(parent) {
+                    root = None;
+                    break;
+                }
+                if approved.contains(&parent) {
+                    root = Some(parent);
+                }
+                parent = parents.get(&parent).copied().unwrap_or_default();
+            }
+            let content = comment_text(&comment.comment_content);
+            if content.is_empty() {
+                return None;
+            }
+            Some(ImportedComment {
+                source_id: source_id(comment.comment_id),
+                parent_source_id: root.map(source_id),
+                slug: slug.to_owned(),
+                author: Some(decode_entities(comment.comment_author.trim())).filter(|author| !author.is_empty()),
+                content,
+                created_at: parse_date(&comment.comment_date_gmt, &comment.comment_date).unwrap_or_else(Local::now),
+            })
+        })
+        .collect()
+}
+
+/// 优先使用 UTC 时间，草稿的 UTC 时间为 0000-00-00 00:00:00，此时使用站点时间
+fn parse_date(gmt: &str, local: &str) -> Option<DateTime<Local>> {
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    NaiveDateTime::parse_from_str(gmt.trim(), FORMAT)
+        .ok()
+        .map(|date| Utc.from_utc_datetime(&date).with_timezone(&Local))
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(local.trim(), FORMAT)
+                .ok()
+                .and_then(|date| Local.from_local_datetime(&date).earliest())
+        })
+}
+
+/// 去掉图片说明的短代码，并为经典编辑器保存的正文补上段落标签
+fn prepare_html(content: &str) -> String {
+    // [caption id="attachment_1" align="aligncenter"]<img ... /> 说明文字[/caption]
+    static CAPTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)\[caption[^\]]*\](.*?)\[/caption\]").unwrap());
+    static EMBED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[embed[^\]]*\](.*?)\[/embed\]").unwrap());
+    let content = CAPTION.replace_all(content, "$1");
+    let content = EMBED.replace_all(&content, r#"<a href="$1">$1</a>"#);
+    autop(&content)
+}
+
+/// 经典编辑器的正文以空行分段，与 WordPress 的 wpautop 一致；区块编辑器的正文已经带有段落标签
+fn autop(content: &str) -> String {
+    static BLOCK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)^<(?:h[1-6]|p|ul|ol|pre|blockquote|table|div|figure|hr|video|audio|iframe)\b").unwrap()
+    });
+    if content.contains("<p>") || content.contains("<p ") || content.contains("<!-- wp:") {
+        return content.to_owned();
+    }
+    content
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            if BLOCK.is_match(paragraph) {
+                paragraph.to_owned()
+            } else {
+                format!("<p>{}</p>", paragraph.replace('\n', "<br />"))
+            }
+        })
+        .join("\n")
+}
+
+/// htmd 会丢弃视频、音频与内嵌框架，转换前替换为占位符，转换后还原为原始的 html
+fn html_to_markdown(html: &str) -> Result<String> {
+    static EMBED: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?is)<(?:video|audio|iframe)\b.*?</(?:video|audio|iframe)>").unwrap());
+    static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"SUWENEMBED(\d+)").unwrap());
+    let mut embeds = Vec::new();
+    let html = EMBED.replace_all(html, |caps: &Captures| {
+        // 保持在同一行内，避免空行打断 markdown 中的 html
+        embeds.push(caps[0].lines().map(str::trim).join(" "));
+        format!("<p>SUWENEMBED{}</p>", embeds.len() - 1)
+    });
+    let markdown = htmd::convert(&html)?;
+    Ok(PLACEHOLDER
+        .replace_all(&markdown, |caps: &Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| embeds.get(idx))
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_owned())
+        })
+        .into_owned())
+}
+
+/// 评论以纯文本展示，去掉标签并还原常见的实体
+fn comment_text(html: &str) -> String {
+    static BREAK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)(?:<br\s*/?>|</p>)\r?\n?").unwrap());
+    static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+    let text = BREAK.replace_all(html, "\n");
+    let text = TAG.replace_all(&text, "");
+    let text = decode_entities(&text);
+    text.lines().map(str::trim_end).join("\n").trim().to_owned()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#039;", "'")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// 站内的相对地址补全为站点地址，其它协议的地址不下载
+fn absolute_url(site: &str, url: &str) -> Option<String> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Some(url.to_owned())
+    } else if let Some(url) = url.strip_prefix("//") {
+        Some(format!("https://{}", url))
+    } else if url.starts_with('/') && !site.is_empty() {
+        Some(format!("{}{}", site, url))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: u64, parent: u64, approved: &str, content: &str) -> schema::Comment {
+        schema::Comment {
+            comment_id: id,
+            comment_author: format!("user{id}"),
+            comment_date: "2020-01-02 03:04:05".to_owned(),
+            comment_date_gmt: String::new(),
+            comment_content: content.to_owned(),
+            comment_approved: approved.to_owned(),
+            comment_type: String::new(),
+            comment_parent: parent,
+        }
+    }
+
+    #[test]
+    fn test_prepare_html() {
+        let html = prepare_html(
+            "第一段\n第二行\n\n[caption id=\"attachment_1\"]<img src=\"/a.png\" alt=\"a\" /> 说明[/caption]\n\n<video \
+             src=\"/v.mp4\"></video>",
+        );
+        assert_eq!(
+            html,
+            "<p>第一段<br />第二行</p>\n<p><img src=\"/a.png\" alt=\"a\" /> 说明</p>\n<video src=\"/v.mp4\"></video>"
+        );
+        // 区块编辑器的正文已经分段
+        assert_eq!(
+            prepare_html("<!-- wp:paragraph -->\n<p>a</p>"),
+            "<!-- wp:paragraph -->\n<p>a</p>"
+        );
+    }
+
+    #[test]
+    fn test_html_to_markdown() {
+        let markdown =
+            html_to_markdown("<p><img src=\"/a.png\" alt=\"a\" /></p>\n<video\n src=\"/v.mp4\">\n</video>").unwrap();
+        assert!(markdown.contains("![a](/a.png)"));
+        assert!(markdown.contains("<video src=\"/v.mp4\"> </video>"));
+    }
+
+    #[test]
+    fn test_comment_text() {
+        assert_eq!(
+            comment_text("<p>很好&amp;不错<br />\n<a href=\"#\">链接</a></p>"),
+            "很好&不错\n链接"
+        );
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        let site = "https://example.com";
+        assert_eq!(
+            absolute_url(site, "/a.png").as_deref(),
+            Some("https://example.com/a.png")
+        );
+        assert_eq!(
+            absolute_url(site, "//cdn.com/a.png").as_deref(),
+            Some("https://cdn.com/a.png")
+        );
+        assert_eq!(
+            absolute_url(site, "http://cdn.com/a.png").as_deref(),
+            Some("http://cdn.com/a.png")
+        );
+        assert_eq!(absolute_url(site, "a.png"), None);
+        assert_eq!(absolute_url("", "/a.png"), None);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // 草稿的 UTC 时间为 0000-00-00 00:00:00
+        let draft = parse_date("0000-00-00 00:00:00", "2020-01-02 03:04:05").unwrap();
+        assert_eq!(draft.naive_local().to_string(), "2020-01-02 03:04:05");
+        let published = parse_date("2020-01-02 03:04:05", "").unwrap();
+        assert_eq!(
+            published.with_timezone(&Utc).naive_utc().to_string(),
+            "2020-01-02 03:04:05"
+        );
+        assert!(parse_date("", "").is_none());
+    }
+
+    #[test]
+    fn test_collect_comments() {
+        let comments = collect_comments(
+            "https://example.com",
+            "post",
+            vec![
+                comment(1, 0, "1", "顶层"),
+                comment(2, 1, "1", "回复顶层"),
+                comment(3, 2, "1", "回复的回复"),
+                // 未审核的评论不导入，其回复挂到更上层的已审核评论下
+                comment(4, 1, "0", "未审核"),
+                comment(5, 4, "1", "回复未审核"),
+                comment(6, 7, "1", "循环引用"),
+                comment(7, 6, "1", "循环引用"),
+                comment(8, 0, "1", "<p> </p>"),
+            ],
+        );
+        let flattened = comments
+            .iter()
+            .map(|comment| {
+                (
+                    comment.source_id.as_str(),
+                    comment.parent_source_id.as_deref(),
+                    comment.content.as_str(),
+                )
+            })
+            .collect_vec();
+        assert_eq!(
+            flattened,
+            [
+                ("wordpress:https://example.com:1", None, "顶层"),
+                (
+                    "wordpress:https://example.com:2",
+                    Some("wordpress:https://example.com:1"),
+                    "回复顶层"
+                ),
+                (
+                    "wordpress:https://example.com:3",
+                    Some("wordpress:https://example.com:1"),
+                    "回复的回复"
+                ),
+                (
+                    "wordpress:https://example.com:5",
+                    Some("wordpress:https://example.com:1"),
+                    "回复未审核"
+                ),
+                ("wordpress:https://example.com:6", None, "循环引用"),
+                ("wordpress:https://example.com:7", None, "循环引用"),
+            ]
+        );
+        assert!(comments.iter().all(|comment| comment.slug == "post"));
+        assert_eq!(comments[0].author.as_deref(), Some("user1"));
+    }
+}
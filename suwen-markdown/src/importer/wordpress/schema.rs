@@ -0,0 +1,83 @@
+use serde::Deserialize;
+
+// quick-xml 按不带命名空间前缀的本地名匹配字段，如 wp:post_id 对应 post_id
+
+#[derive(Deserialize, Debug)]
+pub(super) struct Rss {
+    pub channel: Channel,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct Channel {
+    #[serde(default)]
+    pub base_site_url: String,
+    #[serde(rename = "item", default)]
+    pub items: Vec<Item>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct Item {
+    #[serde(default)]
+    pub title: String,
+    /// 正文 content:encoded
+    #[serde(default)]
+    pub encoded: String,
+    pub post_id: u64,
+    #[serde(default)]
+    pub post_name: String,
+    #[serde(default)]
+    pub post_type: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub post_date: String,
+    #[serde(default)]
+    pub post_date_gmt: String,
+    #[serde(default)]
+    pub post_modified: String,
+    #[serde(default)]
+    pub post_modified_gmt: String,
+    pub attachment_url: Option<String>,
+    #[serde(rename = "category", default)]
+    pub categories: Vec<Category>,
+    #[serde(rename = "postmeta", default)]
+    pub meta: Vec<PostMeta>,
+    #[serde(rename = "comment", default)]
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct Category {
+    #[serde(rename = "@domain", default)]
+    pub domain: String,
+    #[serde(rename = "@nicename", default)]
+    pub nicename: String,
+    #[serde(rename = "$text", default)]
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct PostMeta {
+    pub meta_key: String,
+    #[serde(default)]
+    pub meta_value: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub(super) struct Comment {
+    pub comment_id: u64,
+    #[serde(default)]
+    pub comment_author: String,
+    #[serde(default)]
+    pub comment_date: String,
+    #[serde(default)]
+    pub comment_date_gmt: String,
+    #[serde(default)]
+    pub comment_content: String,
+    #[serde(default)]
+    pub comment_approved: String,
+    #[serde(default)]
+    pub comment_type: String,
+    #[serde(default)]
+    pub comment_parent: u64,
+}
@@ -3,24 +3,17 @@ mod schema;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, LazyLock};
 
-use anyhow::{Context, Error, Result, bail};
-use futures::stream::FuturesUnordered;
-use futures::{StreamExt, TryStreamExt};
+use anyhow::{Context, Result, bail};
 use lol_html::{HtmlRewriter, Settings, element};
-use pathdiff::diff_paths;
 use pulldown_cmark::{Event, Tag};
 use pulldown_cmark_to_cmark::cmark_resume;
-use regex::{Captures, Regex};
 use suwen_config::CONFIG;
-use tokio::fs::{File, read_to_string};
-use tokio::io;
-use tokio::sync::Semaphore;
-use tokio_util::io::StreamReader;
+use tokio::fs::read_to_string;
 use yaml_rust2::YamlLoader;
 
 use crate::importer::Markdown;
+use crate::importer::download::batch_download_replace;
 use crate::importer::xlog::schema::Content;
 use crate::parse_markdown;
 
@@ -51,6 +44,8 @@ async fn handle_short(content: Content, slug: String, output: &Path, obj_output:
         created_at: Some(content.created_at),
         updated_at: Some(content.updated_at),
         published_at: Some(content.published_at),
+        source_commit: None,
+        media: Vec::new(),
     })
 }
 
@@ -132,6 +127,10 @@ async fn handle_post(content: Content, slug: String, output: &Path, obj_output:
         created_at: Some(content.created_at),
         updated_at: Some(content.updated_at),
         published_at: Some(content.published_at),
+        series: None,
+        aliases: Vec::new(),
+        source_commit: None,
+        media: Vec::new(),
     })
 }
 
@@ -190,74 +189,6 @@ async fn init_cover_images(content: &Content, slug: &str, output: &Path, obj_out
     cover_images
 }
 
-// 批量下载并替换，生成 Vec<(url, new_url)>
-async fn batch_download_replace<T>(
-    old_new_pair: impl IntoIterator<Item = (T, PathBuf)>,
-    output: &Path,
-) -> Vec<Result<(T, String)>>
-where
-    T: AsRef<str>,
-{
-    let semaphore = Arc::new(Semaphore::new(8));
-    let tasks = old_new_pair
-        .into_iter()
-        .map(|(url, target)| {
-            let semaphore = semaphore.clone();
-            async move {
-                let _permit = semaphore.acquire().await;
-                let res = download_replace(url.as_ref(), output, &target).await?;
-                Ok::<_, Error>((url, res))
-            }
-        })
-        .collect::<FuturesUnordered<_>>();
-    tasks.collect::<Vec<_>>().await
-}
-
-/// 从 URL 下载文件，并返回替换后的相对路径，仅当 url 为空时返回 Error
-async fn download_replace(url: &str, output: &Path, target: &Path) -> Result<String> {
-    // 替换 ipfs:// 格式的链接为普通 URL
-    static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"ipfs://([a-zA-Z0-9]+)").unwrap());
-    let url = RE.replace_all(url, |caps: &Captures| {
-        format!("https://ipfs.crossbell.io/ipfs/{}", &caps[1])
-    });
-    if url.is_empty() {
-        bail!("Invalid URL");
-    }
-    Ok(match download(&url, target).await {
-        // 成功，替换成下载的文件相对文章的相对路径
-        Ok(file) => diff_paths(&file, output).unwrap().to_string_lossy().to_string(),
-        // 失败，添加死链标记
-        Err(err) => {
-            error!("Failed to download {}: {}", url, err);
-            format!("$dead_link/{}", url)
-        }
-    })
-}
-
-// 下载某个 URL 到指定路径，返回下载成功的路径（在指定路径上附加拓展名）
-async fn download(url: &str, target: &Path) -> Result<PathBuf> {
-    static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
-        reqwest::Client::builder()
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:141.0) Gecko/20100101 Firefox/141.0")
-            .build()
-            .unwrap()
-    });
-    let resp = CLIENT.get(url).send().await.context("Failed to download")?;
-    let extension = resp
-        .headers()
-        .get(reqwest::header::CONTENT_TYPE)
-        .and_then(|h| h.to_str().ok())
-        .and_then(mime_guess::get_mime_extensions_str)
-        .and_then(|exts| exts.first())
-        .copied()
-        .context("Failed to parse mime type")?;
-    let download_file = target.with_extension(extension);
-    let mut file = File::create(&download_file).await.context("Failed to create file")?;
-    let mut stream_reader = StreamReader::new(resp.bytes_stream().map_err(std::io::Error::other));
-    io::copy(&mut stream_reader, &mut file).await?;
-    Ok(download_file)
-}
-
 // 扫描 html，取出所有的视频链接
 async fn collect_videos(html: &str) -> Result<Vec<String>> {
     let mut videos = Vec::new();
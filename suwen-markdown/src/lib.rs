@@ -3,13 +3,24 @@ extern crate tracing;
 
 pub mod importer;
 
-pub use markdown::Markdown;
-pub use processor::{MarkdownProcessor, UploadedMedia};
-pub use watcher::{MarkdownChange, MarkdownWatcher};
+pub use files::{find_markdown_file, markdown_files};
+pub use git::GitSource;
+pub use markdown::{Markdown, Series};
+pub use processor::{MarkdownProcessor, StoredMedia, UploadedMedia};
+pub use source::{ContentSource, MarkdownChange};
+pub use store::{LocalStore, MediaStore, S3Store, StoredObject, media_store, safe_relative_path};
+pub use watcher::MarkdownWatcher;
 
+mod files;
+mod git;
 mod highlighter;
+mod imaging;
+mod manifest;
 mod markdown;
 mod processor;
+mod source;
+mod store;
+mod video;
 mod watcher;
 
 use std::fs::create_dir_all;
@@ -18,10 +29,13 @@ use std::sync::LazyLock;
 
 use anyhow::Result;
 use pulldown_cmark::{Event, Options, Tag, TagEnd};
+use suwen_config::{CONFIG, LocalStoreConfig, MediaStoreConfig};
 
 pub static UPLOAD_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
-    let env_path = std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".into());
-    let path = PathBuf::from(env_path);
+    let path = match &CONFIG.media_store {
+        MediaStoreConfig::Local(LocalStoreConfig { path: Some(path), .. }) => PathBuf::from(path),
+        _ => PathBuf::from(std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".into())),
+    };
     if !path.exists() {
         create_dir_all(&path).expect("Failed to create upload directory");
     }
@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use dirs::config_dir;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::StoredMedia;
+use crate::processor::VIDEO_EXTENSIONS;
+
+/// 本地文件的修改时间与大小，任一变化时视为新的文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileStamp {
+    modified: Option<SystemTime>,
+    size: u64,
+}
+
+impl From<&Metadata> for FileStamp {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// 远程媒体没有修改时间，地址不变即视为同一文件
+    stamp: Option<FileStamp>,
+    media: StoredMedia,
+    /// 最近一次确认文件仍在存储中时的清理代数
+    #[serde(default)]
+    generation: u64,
+}
+
+/// 已处理的 markdown 文件，文件本身与引用的本地媒体均未变化时，启动扫描无需重新处理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SourceEntry {
+    stamp: FileStamp,
+    slug: String,
+    media: Vec<(PathBuf, FileStamp)>,
+}
+
+/// 清单格式的版本，读取旧版本的清单时丢弃需要重新处理的记录
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestData {
+    #[serde(default)]
+    version: u32,
+    entries: HashMap<String, ManifestEntry>,
+    #[serde(default)]
+    sources: HashMap<PathBuf, SourceEntry>,
+}
+
+impl Default for ManifestData {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            entries: HashMap::new(),
+            sources: HashMap::new(),
+        }
+    }
+}
+
+impl ManifestData {
+    fn parse(data: &[u8]) -> Result<Self> {
+        // 旧版本的清单只包含媒体记录
+        let mut manifest = serde_json::from_slice(data).or_else(|_| {
+            anyhow::Ok(Self {
+                version: 0,
+                entries: serde_json::from_slice(data)?,
+                sources: HashMap::new(),
+            })
+        })?;
+        manifest.upgrade();
+        Ok(manifest)
+    }
+
+    fn upgrade(&mut self) {
+        if self.version < 1 {
+            // 版本 1 开始为视频生成封面与时长，之前上传的视频需要重新处理，引用它们的文件也不能跳过
+            self.entries.retain(|_, entry| !is_video_url(&entry.media.url));
+            self.sources.clear();
+        }
+        self.version = MANIFEST_VERSION;
+    }
+}
+
+fn is_video_url(url: &str) -> bool {
+    url.rsplit_once('.')
+        .is_some_and(|(_, ext)| VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// 记录原始媒体与上传结果的清单，重启后命中清单的媒体无需重新下载、计算哈希或查询存储
+///
+/// 清理孤立媒体时会更新清单旁的清理代数，其它进程中早于该代数的记录在命中时需要重新确认文件存在
+pub(crate) struct MediaManifest {
+    path: PathBuf,
+    data: Mutex<ManifestData>,
+    gc_generation: AtomicU64,
+    dirty: Mutex<bool>,
+    save_lock: tokio::sync::Mutex<()>,
+}
+
+impl MediaManifest {
+    pub(crate) async fn load() -> Self {
+        let path = config_dir()
+            .map(|path| path.join("suwen").join("media-manifest.json"))
+            .unwrap_or_else(|| "media-manifest.json".into());
+        Self::load_from(path).await
+    }
+
+    async fn load_from(path: PathBuf) -> Self {
+        let data = match tokio::fs::read(&path).await {
+            Ok(data) => ManifestData::parse(&data).unwrap_or_else(|e| {
+                warn!("Failed to parse media manifest {:?}, starting from empty: {}", path, e);
+                ManifestData::default()
+            }),
+            Err(_) => ManifestData::default(),
+        };
+        let manifest = Self {
+            path,
+            data: Mutex::new(data),
+            gc_generation: AtomicU64::new(0),
+            dirty: Mutex::new(false),
+            save_lock: tokio::sync::Mutex::new(()),
+        };
+        manifest.refresh_gc_generation().await;
+        manifest
+    }
+
+    /// 查询已上传的结果，本地文件的修改时间或大小变化时不命中，并返回记录是否晚于最近一次清理
+    pub(crate) fn get(&self, slug: &str, source: &str, stamp: Option<FileStamp>) -> Option<(StoredMedia, bool)> {
+        let gc_generation = self.gc_generation.load(Ordering::Relaxed);
+        self.data
+            .lock()
+            .entries
+            .get(&Self::key(slug, source))
+            .filter(|entry| entry.stamp == stamp)
+            .map(|entry| (entry.media.clone(), entry.generation >= gc_generation))
+    }
+
+    pub(crate) fn insert(&self, slug: &str, source: &str, stamp: Option<FileStamp>, media: StoredMedia) {
+        let entry = ManifestEntry {
+            stamp,
+            media,
+            generation: self.gc_generation.load(Ordering::Relaxed),
+        };
+        self.data.lock().entries.insert(Self::key(slug, source), entry);
+        *self.dirty.lock() = true;
+    }
+
+    /// 确认文件仍在存储中后更新记录的清理代数
+    pub(crate) fn mark_verified(&self, slug: &str, source: &str) {
+        let gc_generation = self.gc_generation.load(Ordering::Relaxed);
+        if let Some(entry) = self.data.lock().entries.get_mut(&Self::key(slug, source)) {
+            entry.generation = gc_generation;
+            *self.dirty.lock() = true;
+        }
+    }
+
+    /// 读取其它进程（如 suwen media gc）更新的清理代数
+    pub(crate) async fn refresh_gc_generation(&self) {
+        let generation = tokio::fs::read_to_string(self.gc_generation_path())
+            .await
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0);
+        self.gc_generation.fetch_max(generation, Ordering::Relaxed);
+    }
+
+    /// 清理孤立媒体后更新清理代数，使所有进程中已有的记录重新确认
+    pub(crate) async fn bump_gc_generation(&self) -> Result<()> {
+        self.refresh_gc_generation().await;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64);
+        let generation = now.max(self.gc_generation.load(Ordering::Relaxed) + 1);
+        self.gc_generation.store(generation, Ordering::Relaxed);
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(self.gc_generation_path(), generation.to_string()).await?;
+        Ok(())
+    }
+
+    fn gc_generation_path(&self) -> PathBuf {
+        self.path.with_extension("gc")
+    }
+
+    /// 删除指向指定地址的记录，存储中的文件被清理后不能再命中
+    pub(crate) fn remove_urls(&self, urls: &HashSet<String>) {
+        let mut data = self.data.lock();
+        let len = data.entries.len();
+        data.entries.retain(|_, entry| !urls.contains(&entry.media.url));
+        if data.entries.len() != len {
+            *self.dirty.lock() = true;
+        }
+    }
+
+    /// 文件及其引用的本地媒体与上次处理时相同时返回当时的 slug
+    pub(crate) async fn unchanged_source(&self, path: &Path) -> Option<String> {
+        let entry = self.data.lock().sources.get(path).cloned()?;
+        let unchanged = async |path: &Path, stamp: FileStamp| {
+            tokio::fs::metadata(path)
+                .await
+                .is_ok_and(|metadata| FileStamp::from(&metadata) == stamp)
+        };
+        if !unchanged(path, entry.stamp).await {
+            return None;
+        }
+        for (media_path, stamp) in &entry.media {
+            if !unchanged(media_path, *stamp).await {
+                return None;
+            }
+        }
+        Some(entry.slug)
+    }
+
+    pub(crate) fn insert_source(&self, path: &Path, stamp: FileStamp, slug: &str, media: Vec<(PathBuf, FileStamp)>) {
+        let entry = SourceEntry {
+            stamp,
+            slug: slug.to_owned(),
+            media,
+        };
+        self.data.lock().sources.insert(path.to_path_buf(), entry);
+        *self.dirty.lock() = true;
+    }
+
+    /// 删除指定 slug 的文件记录，下次启动时重新处理
+    pub(crate) fn remove_source(&self, slug: &str) {
+        let mut data = self.data.lock();
+        let len = data.sources.len();
+        data.sources.retain(|_, entry| entry.slug != slug);
+        if data.sources.len() != len {
+            *self.dirty.lock() = true;
+        }
+    }
+
+    /// 有新记录时写回磁盘，先写入临时文件再替换，避免中断时损坏清单
+    pub(crate) async fn save(&self) -> Result<()> {
+        let _guard = self.save_lock.lock().await;
+        let data = {
+            let mut dirty = self.dirty.lock();
+            if !*dirty {
+                return Ok(());
+            }
+            *dirty = false;
+            serde_json::to_vec(&*self.data.lock())?
+        };
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        let result = async {
+            tokio::fs::write(&tmp_path, data).await?;
+            tokio::fs::rename(&tmp_path, &self.path).await
+        }
+        .await;
+        if result.is_err() {
+            *self.dirty.lock() = true;
+        }
+        Ok(result?)
+    }
+
+    /// 同一文件被不同文章引用时上传到不同的路径，因此以 slug 与来源共同作为键
+    fn key(slug: &str, source: &str) -> String {
+        format!("{}\n{}", slug, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn media(url: &str) -> StoredMedia {
+        StoredMedia {
+            url: url.to_owned(),
+            width: Some(16),
+            height: Some(9),
+            placeholder: None,
+            variants: Vec::new(),
+            hash: "hash".to_owned(),
+            poster: None,
+            duration: None,
+        }
+    }
+
+    fn stamp(size: u64) -> FileStamp {
+        FileStamp {
+            modified: Some(SystemTime::UNIX_EPOCH),
+            size,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stamp_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = MediaManifest::load_from(dir.path().join("manifest.json")).await;
+        manifest.insert("slug", "/a.png", Some(stamp(1)), media("https://cdn/a.webp"));
+        manifest.insert("slug", "https://remote/b.png", None, media("https://cdn/b.webp"));
+
+        assert!(manifest.get("slug", "/a.png", Some(stamp(1))).is_some());
+        assert!(manifest.get("slug", "/a.png", Some(stamp(2))).is_none());
+        assert!(manifest.get("slug", "/a.png", None).is_none());
+        assert!(manifest.get("other", "/a.png", Some(stamp(1))).is_none());
+        assert!(manifest.get("slug", "https://remote/b.png", None).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_save_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("suwen").join("manifest.json");
+        let manifest = MediaManifest::load_from(path.clone()).await;
+        manifest.insert("slug", "/a.png", Some(stamp(1)), media("https://cdn/a.webp"));
+        manifest.save().await.unwrap();
+
+        let loaded = MediaManifest::load_from(path.clone()).await;
+        let (media, verified) = loaded.get("slug", "/a.png", Some(stamp(1))).unwrap();
+        assert!(verified);
+        assert_eq!(
+            (media.url.as_str(), media.width, media.height),
+            ("https://cdn/a.webp", Some(16), Some(9))
+        );
+
+        // 兼容只包含媒体记录的旧版本清单
+        let legacy = serde_json::to_vec(&loaded.data.lock().entries).unwrap();
+        std::fs::write(&path, legacy).unwrap();
+        let loaded = MediaManifest::load_from(path).await;
+        assert!(loaded.get("slug", "/a.png", Some(stamp(1))).is_some());
+    }
+
+    #[test]
+    fn test_upgrade_drops_videos() {
+        let mut data = ManifestData::default();
+        data.entries.insert(
+            MediaManifest::key("slug", "/a.png"),
+            ManifestEntry {
+                stamp: Some(stamp(1)),
+                media: media("https://cdn/slug/1.webp"),
+                generation: 0,
+            },
+        );
+        data.entries.insert(
+            MediaManifest::key("slug", "/b.MOV"),
+            ManifestEntry {
+                stamp: Some(stamp(1)),
+                media: media("https://cdn/slug/2.MOV"),
+                generation: 0,
+            },
+        );
+        data.sources.insert(
+            PathBuf::from("/notes/post.md"),
+            SourceEntry {
+                stamp: stamp(1),
+                slug: "slug".to_owned(),
+                media: Vec::new(),
+            },
+        );
+        let mut json = serde_json::to_value(&data).unwrap();
+        assert_eq!(
+            ManifestData::parse(json.to_string().as_bytes()).unwrap().entries.len(),
+            2
+        );
+
+        // 版本 1 之前上传的视频没有封面，丢弃后重新处理
+        json.as_object_mut().unwrap().remove("version");
+        let upgraded = ManifestData::parse(json.to_string().as_bytes()).unwrap();
+        assert_eq!(upgraded.version, MANIFEST_VERSION);
+        assert_eq!(
+            upgraded.entries.keys().collect_vec(),
+            [&MediaManifest::key("slug", "/a.png")]
+        );
+        assert!(upgraded.sources.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dirty_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let manifest = MediaManifest::load_from(path.clone()).await;
+        manifest.save().await.unwrap();
+        assert!(!path.exists());
+
+        manifest.insert("slug", "/a.png", Some(stamp(1)), media("https://cdn/a.webp"));
+        manifest.save().await.unwrap();
+        assert!(path.exists());
+        // 没有新的变化时不会重复写入
+        std::fs::remove_file(&path).unwrap();
+        manifest.save().await.unwrap();
+        assert!(!path.exists());
+
+        manifest.remove_urls(&HashSet::from(["https://cdn/other.webp".to_owned()]));
+        manifest.save().await.unwrap();
+        assert!(!path.exists());
+        manifest.remove_urls(&HashSet::from(["https://cdn/a.webp".to_owned()]));
+        manifest.save().await.unwrap();
+        assert!(path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_gc_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        let manifest = MediaManifest::load_from(path.clone()).await;
+        manifest.insert("slug", "/a.png", Some(stamp(1)), media("https://cdn/a.webp"));
+        assert!(manifest.get("slug", "/a.png", Some(stamp(1))).unwrap().1);
+
+        // 另一个进程清理媒体后，已有的记录需要重新确认
+        let other = MediaManifest::load_from(path).await;
+        other.bump_gc_generation().await.unwrap();
+        assert!(manifest.get("slug", "/a.png", Some(stamp(1))).unwrap().1);
+        manifest.refresh_gc_generation().await;
+        assert!(!manifest.get("slug", "/a.png", Some(stamp(1))).unwrap().1);
+
+        manifest.mark_verified("slug", "/a.png");
+        assert!(manifest.get("slug", "/a.png", Some(stamp(1))).unwrap().1);
+        manifest.insert("slug", "/b.png", Some(stamp(1)), media("https://cdn/b.webp"));
+        assert!(manifest.get("slug", "/b.png", Some(stamp(1))).unwrap().1);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let (markdown, image) = (dir.path().join("post.md"), dir.path().join("a.png"));
+        std::fs::write(&markdown, "post").unwrap();
+        std::fs::write(&image, "image").unwrap();
+        let stamp_of = |path: &Path| FileStamp::from(&std::fs::metadata(path).unwrap());
+        let manifest = MediaManifest::load_from(dir.path().join("manifest.json")).await;
+        assert!(manifest.unchanged_source(&markdown).await.is_none());
+
+        manifest.insert_source(
+            &markdown,
+            stamp_of(&markdown),
+            "post",
+            vec![(image.clone(), stamp_of(&image))],
+        );
+        assert_eq!(manifest.unchanged_source(&markdown).await.as_deref(), Some("post"));
+        // 引用的本地媒体变化时同样需要重新处理
+        std::fs::write(&image, "new image").unwrap();
+        assert!(manifest.unchanged_source(&markdown).await.is_none());
+
+        manifest.insert_source(
+            &markdown,
+            stamp_of(&markdown),
+            "post",
+            vec![(image.clone(), stamp_of(&image))],
+        );
+        manifest.remove_source("post");
+        assert!(manifest.unchanged_source(&markdown).await.is_none());
+    }
+}
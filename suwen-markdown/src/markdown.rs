@@ -8,17 +8,18 @@ use std::sync::LazyLock;
 use anyhow::{Context, Result, anyhow, bail};
 use chrono::{DateTime, Local};
 use itertools::Itertools;
+use lol_html::html_content::ContentType;
 use lol_html::{HtmlRewriter, Settings, element};
 use parking_lot::Mutex;
 use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd, html};
 use pulldown_cmark_to_cmark::cmark_resume;
 use serde::{Deserialize, Serialize};
-use suwen_config::Lang;
-use suwen_entity::{Toc, TocItem};
+use suwen_config::{CONFIG, Lang};
+use suwen_entity::{MediaVariant, Toc, TocItem, media};
 use twox_hash::XxHash3_64;
 
 use crate::highlighter::Highlighter;
-use crate::{UploadedMedia, parse_markdown};
+use crate::{StoredMedia, UploadedMedia, parse_markdown};
 
 static HIGHLIGHTER: LazyLock<Mutex<Highlighter>> = LazyLock::new(|| Mutex::new(Highlighter::new()));
 
@@ -37,8 +38,19 @@ pub enum Markdown {
         created_at: Option<DateTime<Local>>,
         updated_at: Option<DateTime<Local>>,
         published_at: Option<DateTime<Local>>,
+        #[serde(default)]
+        series: Option<Series>,
+        /// 文章曾经使用的 slug，访问时跳转到本文
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        aliases: Vec<String>,
         #[serde(skip)]
         lang: Lang,
+        /// 内容来自 git 仓库时，最后一次修改该文件的提交
+        #[serde(skip)]
+        source_commit: Option<String>,
+        /// 处理文件时上传的媒体，由数据库记录其尺寸与不同宽度的版本
+        #[serde(skip)]
+        media: Vec<StoredMedia>,
     },
     Short {
         #[serde(default)]
@@ -53,9 +65,20 @@ pub enum Markdown {
         published_at: Option<DateTime<Local>>,
         #[serde(skip)]
         lang: Lang,
+        #[serde(skip)]
+        source_commit: Option<String>,
+        #[serde(skip)]
+        media: Vec<StoredMedia>,
     },
 }
 
+/// 文章所属的系列，order 决定文章在系列中的顺序
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    pub name: String,
+    pub order: i32,
+}
+
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub enum MediaResource {
     Image(String),
@@ -109,6 +132,20 @@ impl Markdown {
         }
     }
 
+    pub fn series(&self) -> Option<&Series> {
+        match self {
+            Markdown::Article { series, .. } => series.as_ref(),
+            Markdown::Short { .. } => None,
+        }
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        match self {
+            Markdown::Article { aliases, .. } => aliases,
+            Markdown::Short { .. } => &[],
+        }
+    }
+
     pub fn created_at(&self) -> Option<DateTime<Local>> {
         match self {
             Markdown::Article { created_at, .. } | Markdown::Short { created_at, .. } => *created_at,
@@ -127,6 +164,40 @@ impl Markdown {
         }
     }
 
+    pub fn source_commit(&self) -> Option<&str> {
+        match self {
+            Markdown::Article { source_commit, .. } | Markdown::Short { source_commit, .. } => source_commit.as_deref(),
+        }
+    }
+
+    pub fn media(&self) -> &[StoredMedia] {
+        match self {
+            Markdown::Article { media, .. } | Markdown::Short { media, .. } => media,
+        }
+    }
+
+    /// 记录来源提交，并在 front matter 未声明时间时以提交时间作为创建与更新时间
+    pub(crate) fn set_source_commit(&mut self, commit: String, first: DateTime<Local>, last: DateTime<Local>) {
+        match self {
+            Markdown::Article {
+                source_commit,
+                created_at,
+                updated_at,
+                ..
+            }
+            | Markdown::Short {
+                source_commit,
+                created_at,
+                updated_at,
+                ..
+            } => {
+                *source_commit = Some(commit);
+                created_at.get_or_insert(first);
+                updated_at.get_or_insert(last);
+            }
+        }
+    }
+
     pub fn should_publish(&self) -> bool {
         match self {
             // 如果显式声明 publish = false，跳过发布
@@ -151,10 +222,15 @@ impl Markdown {
         hasher.write(self.title().as_bytes());
         hasher.write(self.content().as_bytes());
         hasher.write(self.tags().join(",").as_bytes());
+        // 仅在设置了系列时参与计算，保证未使用系列的文章哈希保持不变
+        if let Some(series) = self.series() {
+            hasher.write(series.name.as_bytes());
+            hasher.write(&series.order.to_le_bytes());
+        }
         format!("v1:{:x}/{}", hasher.finish(), self.lang())
     }
 
-    pub(super) fn to_string(&self) -> Result<String> {
+    pub fn to_string(&self) -> Result<String> {
         let metadata = serde_json::to_string_pretty(self)?;
         match self {
             Markdown::Article { content, .. } | Markdown::Short { content, .. } => {
@@ -163,14 +239,9 @@ impl Markdown {
         }
     }
 
-    pub(super) fn from_string(input: &str, lang: Lang) -> Result<Self> {
-        let parts = input.splitn(3, "---\n").collect::<Vec<_>>();
-        if parts.len() != 3 {
-            bail!("Invalid markdown format: missing metadata or content");
-        }
-        let mut metadata: Markdown = serde_json::from_str(parts[1]).or_else(|_| serde_yaml::from_str(parts[1]))?;
-        let article = parts[2].to_string();
-        match &mut metadata {
+    /// 设置正文与语言，用于由 front matter 与正文分别构造的 markdown
+    pub fn with_content(mut self, article: String, lang: Lang) -> Self {
+        match &mut self {
             Markdown::Article {
                 content, lang: m_lang, ..
             }
@@ -181,7 +252,16 @@ impl Markdown {
                 *m_lang = lang;
             }
         }
-        Ok(metadata)
+        self
+    }
+
+    pub(super) fn from_string(input: &str, lang: Lang) -> Result<Self> {
+        let parts = input.splitn(3, "---\n").collect::<Vec<_>>();
+        if parts.len() != 3 {
+            bail!("Invalid markdown format: missing metadata or content");
+        }
+        let metadata: Markdown = serde_json::from_str(parts[1]).or_else(|_| serde_yaml::from_str(parts[1]))?;
+        Ok(metadata.with_content(parts[2].to_string(), lang))
     }
 
     pub async fn from_file(path: impl AsRef<Path>, lang: Lang) -> Result<Self> {
@@ -190,7 +270,12 @@ impl Markdown {
             bail!("File {:?} does not have .md extension", path);
         }
         let content = tokio::fs::read_to_string(path).await?;
-        let mut markdown = Self::from_string(&content, lang)?;
+        Self::from_string_at(&content, path, lang)
+    }
+
+    /// 解析位于 path 的 markdown 内容，front matter 未声明 slug 时使用文件名
+    pub(crate) fn from_string_at(input: &str, path: &Path, lang: Lang) -> Result<Self> {
+        let mut markdown = Self::from_string(input, lang)?;
         if markdown.slug().is_empty()
             && let Some(new_slug) = path.file_stem().and_then(|s| s.to_str())
         {
@@ -214,6 +299,29 @@ impl Markdown {
         Ok(images.into_iter().unique().collect())
     }
 
+    /// 提取正文中没有替代文本的图片，如 ![](...)
+    pub fn extract_images_without_alt(&self) -> Result<Vec<String>> {
+        let mut images = Vec::new();
+        let mut current = None;
+        for event in parse_markdown(self.content())? {
+            match event {
+                Event::Start(Tag::Image { dest_url, .. }) => current = Some((dest_url.to_string(), false)),
+                Event::Text(text) | Event::Code(text) if !text.trim().is_empty() => {
+                    if let Some((_, has_alt)) = current.as_mut() {
+                        *has_alt = true;
+                    }
+                }
+                Event::End(TagEnd::Image) => {
+                    if let Some((url, false)) = current.take() {
+                        images.push(url);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(images.into_iter().unique().collect())
+    }
+
     pub fn extract_resources(&self) -> Result<Vec<MediaResource>> {
         let events = parse_markdown(self.content())?;
         let resources = Rc::new(RefCell::new(
@@ -269,12 +377,12 @@ impl Markdown {
         }
         let uploaded_media = uploaded_media
             .into_iter()
-            .map(|m| (m.original_url, m.new_url))
+            .map(|m| (m.original_url, m.media))
             .collect::<HashMap<_, _>>();
         if let Some(cover_images) = self.cover_images_ref() {
             for img in cover_images.iter_mut() {
-                if let Some(new_url) = uploaded_media.get(img.as_str()) {
-                    *img = new_url.clone();
+                if let Some(media) = uploaded_media.get(img.as_str()) {
+                    *img = media.url.clone();
                 }
             }
         }
@@ -283,8 +391,8 @@ impl Markdown {
         for event in events.iter_mut() {
             match event {
                 Event::Start(Tag::Image { dest_url, .. }) => {
-                    if let Some(new_url) = uploaded_media.get(dest_url.as_ref()) {
-                        *dest_url = new_url.clone().into();
+                    if let Some(media) = uploaded_media.get(dest_url.as_ref()) {
+                        *dest_url = media.url.clone().into();
                         need_update = true;
                     }
                 }
@@ -295,17 +403,17 @@ impl Markdown {
                             element_content_handlers: vec![
                                 element!("img[src]", |el| {
                                     if let Some(src) = el.get_attribute("src")
-                                        && let Some(new_url) = uploaded_media.get(&src)
+                                        && let Some(media) = uploaded_media.get(&src)
                                     {
-                                        el.set_attribute("src", new_url)?;
+                                        el.set_attribute("src", &media.url)?;
                                     }
                                     Ok(())
                                 }),
                                 element!("source[src]", |el| {
                                     if let Some(src) = el.get_attribute("src")
-                                        && let Some(new_url) = uploaded_media.get(&src)
+                                        && let Some(media) = uploaded_media.get(&src)
                                     {
-                                        el.set_attribute("src", new_url)?;
+                                        el.set_attribute("src", &media.url)?;
                                     }
                                     Ok(())
                                 }),
@@ -331,6 +439,11 @@ impl Markdown {
                 }
             }
         }
+        match self {
+            Markdown::Article { media, .. } | Markdown::Short { media, .. } => {
+                *media = uploaded_media.into_values().collect();
+            }
+        }
         Ok(())
     }
 
@@ -372,7 +485,13 @@ impl Markdown {
         Ok(())
     }
 
-    pub fn render_to_html(&self) -> Result<(Option<Toc>, Option<String>)> {
+    /// 渲染为 HTML，media 为图片地址到已记录媒体信息的映射，用于补充图片尺寸、占位图与响应式版本，
+    /// alt_texts 为缺少替代文本的图片补充描述
+    pub fn render_to_html(
+        &self,
+        media: &HashMap<String, media::Model>,
+        alt_texts: &HashMap<String, String>,
+    ) -> Result<(Option<Toc>, Option<String>)> {
         let mut events = parse_markdown(self.content())?;
         let mut toc_item = TocItem {
             id: String::new(),
@@ -414,6 +533,237 @@ impl Markdown {
         let highlighted_events = HIGHLIGHTER.lock().highlight(events.into_iter())?;
         let mut buf = String::new();
         html::push_html(&mut buf, highlighted_events.into_iter());
+        if !media.is_empty() || !alt_texts.is_empty() {
+            buf = enhance_media(&buf, media, alt_texts)?;
+        }
         Ok((Some(toc.into()), Some(buf)))
     }
 }
+
+/// 为缺少描述的图片补充替代文本，补充宽高避免布局偏移，延迟加载并以占位图作为背景；
+/// 存在多种宽度时添加 srcset 与 sizes，有 AVIF 版本时包裹为 picture；
+/// 视频补充封面与宽高，并只预加载元数据
+fn enhance_media(
+    html: &str,
+    media: &HashMap<String, media::Model>,
+    alt_texts: &HashMap<String, String>,
+) -> Result<String> {
+    let sizes = &CONFIG.responsive_images.sizes;
+    let mut video_sources = video_sources(html)?.into_iter();
+    let mut buf = Vec::new();
+    let mut rewriter = HtmlRewriter::new(
+        Settings {
+            element_content_handlers: vec![
+                element!("img[src]", |el| {
+                    let Some(src) = el.get_attribute("src") else {
+                        return Ok(());
+                    };
+                    if let Some(alt_text) = alt_texts.get(&src)
+                        && el.get_attribute("alt").is_none_or(|alt| alt.trim().is_empty())
+                    {
+                        el.set_attribute("alt", alt_text)?;
+                    }
+                    let Some(media) = media.get(&src) else {
+                        return Ok(());
+                    };
+                    if let (Some(width), Some(height)) = (media.width, media.height)
+                        && !el.has_attribute("width")
+                        && !el.has_attribute("height")
+                    {
+                        el.set_attribute("width", &width.to_string())?;
+                        el.set_attribute("height", &height.to_string())?;
+                    }
+                    if !el.has_attribute("loading") {
+                        el.set_attribute("loading", "lazy")?;
+                    }
+                    if let Some(placeholder) = &media.placeholder
+                        && !el.has_attribute("style")
+                    {
+                        el.set_attribute(
+                            "style",
+                            &format!("background:url({}) center/cover no-repeat", placeholder),
+                        )?;
+                    }
+                    let variants = &media.variants.0;
+                    if let Some(srcset) = srcset(variants, "image/webp") {
+                        el.set_attribute("srcset", &srcset)?;
+                        el.set_attribute("sizes", sizes)?;
+                    }
+                    if let Some(srcset) = srcset(variants, "image/avif") {
+                        el.before(
+                            &format!(
+                                r#"<picture><source type="image/avif" srcset="{}" sizes="{}">"#,
+                                srcset, sizes
+                            ),
+                            ContentType::Html,
+                        );
+                        el.after("</picture>", ContentType::Html);
+                    }
+                    Ok(())
+                }),
+                element!("video", move |el| {
+                    let src = video_sources.next().flatten();
+                    if !el.has_attribute("preload") {
+                        el.set_attribute("preload", "metadata")?;
+                    }
+                    let Some(media) = src.and_then(|src| media.get(&src)) else {
+                        return Ok(());
+                    };
+                    if let Some(poster) = &media.poster
+                        && !el.has_attribute("poster")
+                    {
+                        el.set_attribute("poster", poster)?;
+                    }
+                    if let (Some(width), Some(height)) = (media.width, media.height)
+                        && !el.has_attribute("width")
+                        && !el.has_attribute("height")
+                    {
+                        el.set_attribute("width", &width.to_string())?;
+                        el.set_attribute("height", &height.to_string())?;
+                    }
+                    Ok(())
+                }),
+            ],
+            ..Settings::new()
+        },
+        |c: &[u8]| buf.extend_from_slice(c),
+    );
+    rewriter.write(html.as_bytes())?;
+    rewriter.end()?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// 按出现顺序收集每个视频的地址，视频的地址可能写在子元素 source 中，改写 video 标签时还无法读取
+fn video_sources(html: &str) -> Result<Vec<Option<String>>> {
+    if !html.contains("<video") {
+        return Ok(Vec::new());
+    }
+    let sources = RefCell::new(Vec::new());
+    let mut scanner = HtmlRewriter::new(
+        Settings {
+            element_content_handlers: vec![
+                element!("video", |el| {
+                    sources.borrow_mut().push(el.get_attribute("src"));
+                    Ok(())
+                }),
+                element!("video source[src]", |el| {
+                    if let Some(last) = sources.borrow_mut().last_mut()
+                        && last.is_none()
+                    {
+                        *last = el.get_attribute("src");
+                    }
+                    Ok(())
+                }),
+            ],
+            ..Settings::new()
+        },
+        |_: &[u8]| {},
+    );
+    scanner.write(html.as_bytes())?;
+    scanner.end()?;
+    Ok(sources.into_inner())
+}
+
+fn srcset(variants: &[MediaVariant], mime_type: &str) -> Option<String> {
+    let srcset = variants
+        .iter()
+        .filter(|v| v.mime_type == mime_type)
+        .sorted_by_key(|v| v.width)
+        .map(|v| format!("{} {}w", v.url, v.width))
+        .join(", ");
+    (!srcset.is_empty()).then_some(srcset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant(width: u32, mime_type: &str) -> MediaVariant {
+        let ext = mime_type.trim_start_matches("image/");
+        MediaVariant {
+            url: format!("https://cdn/a-{width}w.{ext}"),
+            width,
+            mime_type: mime_type.to_owned(),
+        }
+    }
+
+    fn image(variants: Vec<MediaVariant>) -> HashMap<String, media::Model> {
+        let model = media::Model {
+            url: "https://cdn/a.webp".to_owned(),
+            width: Some(1600),
+            height: Some(900),
+            variants: variants.into(),
+            updated_at: Local::now(),
+            placeholder: None,
+            hash: None,
+            poster: None,
+            duration: None,
+        };
+        HashMap::from([(model.url.clone(), model)])
+    }
+
+    #[test]
+    fn test_extract_images_without_alt() {
+        let markdown = Markdown::from_string(
+            "---\ntype: article\ntitle: t\ntags: []\n---\n![](a.png) ![  ](b.png) ![`code`](c.png)\n\n![描述](d.png) ![](a.png)\n",
+            Lang::default(),
+        )
+        .unwrap();
+        assert_eq!(markdown.extract_images_without_alt().unwrap(), ["a.png", "b.png"]);
+    }
+
+    #[test]
+    fn test_video_sources() {
+        let html = r#"<p>正文</p>
+<video src="a.mp4"><source src="ignored.webm"></video>
+<video controls><source type="video/webm" src="b.webm"><source src="b.mp4"></video>
+<video></video>
+<video><track src="d.vtt"><source src="d.mp4"></video>"#;
+        assert_eq!(
+            video_sources(html).unwrap(),
+            [
+                Some("a.mp4".to_owned()),
+                Some("b.webm".to_owned()),
+                None,
+                Some("d.mp4".to_owned())
+            ]
+        );
+        assert!(video_sources("<img src=\"a.png\">").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_responsive_webp_image() {
+        let media = image(vec![variant(1280, "image/webp"), variant(640, "image/webp")]);
+        let html = enhance_media(r#"<img src="https://cdn/a.webp" alt="a">"#, &media, &HashMap::new()).unwrap();
+        let sizes = &CONFIG.responsive_images.sizes;
+        assert_eq!(
+            html,
+            format!(
+                r#"<img src="https://cdn/a.webp" alt="a" width="1600" height="900" loading="lazy" srcset="https://cdn/a-640w.webp 640w, https://cdn/a-1280w.webp 1280w" sizes="{sizes}">"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_responsive_avif_picture() {
+        let media = image(vec![
+            variant(1280, "image/webp"),
+            variant(1280, "image/avif"),
+            variant(640, "image/avif"),
+            variant(640, "image/webp"),
+        ]);
+        let html = enhance_media(r#"<p><img src="https://cdn/a.webp"></p>"#, &media, &HashMap::new()).unwrap();
+        let sizes = &CONFIG.responsive_images.sizes;
+        assert_eq!(
+            html,
+            format!(
+                concat!(
+                    r#"<p><picture><source type="image/avif" srcset="https://cdn/a-640w.avif 640w, https://cdn/a-1280w.avif 1280w" sizes="{sizes}">"#,
+                    r#"<img src="https://cdn/a.webp" width="1600" height="900" loading="lazy" srcset="https://cdn/a-640w.webp 640w, https://cdn/a-1280w.webp 1280w" sizes="{sizes}">"#,
+                    "</picture></p>"
+                ),
+                sizes = sizes
+            )
+        );
+    }
+}
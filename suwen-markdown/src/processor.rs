@@ -1,31 +1,56 @@
+use std::collections::HashSet;
 use std::hash::Hasher;
-use std::path::Path;
-use std::sync::LazyLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
 
 use anyhow::{Context, Result, bail};
-use aws_config::BehaviorVersion;
-use aws_sdk_s3::error::SdkError;
 use futures::TryStreamExt;
 use futures::stream::FuturesUnordered;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use suwen_config::CONFIG;
+use suwen_entity::MediaVariant;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::{OnceCell, Semaphore};
+use tokio::task::spawn_blocking;
 use twox_hash::XxHash3_64;
 
 use crate::Markdown;
+use crate::imaging::{DecodedImage, strip_metadata};
+use crate::manifest::{FileStamp, MediaManifest};
 use crate::markdown::MediaResource;
+use crate::store::{MediaStore, media_store};
+use crate::video::VideoInfo;
+
+pub(crate) const VIDEO_EXTENSIONS: &[&str] = &["mp4", "webm", "mov", "m4v"];
 
 pub struct MarkdownProcessor {
-    s3_client: aws_sdk_s3::Client,
-    bucket_name: &'static str,
-    prefix: &'static str,
-    s3_domain: &'static str,
+    store: Box<dyn MediaStore>,
+    manifest: MediaManifest,
+}
+
+/// 已上传的媒体，图片会附带宽高、占位图与不同宽度的版本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMedia {
+    pub url: String,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub placeholder: Option<String>,
+    pub variants: Vec<MediaVariant>,
+    /// 原始文件内容的哈希
+    pub hash: String,
+    /// 视频的封面图地址
+    #[serde(default)]
+    pub poster: Option<String>,
+    /// 视频时长，单位为毫秒
+    #[serde(default)]
+    pub duration: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
 pub struct UploadedMedia {
     pub original_url: String,
-    pub new_url: String,
+    pub media: StoredMedia,
 }
 
 impl MarkdownProcessor {
@@ -33,46 +58,94 @@ impl MarkdownProcessor {
         static INSTANCE: OnceCell<MarkdownProcessor> = OnceCell::const_new();
         INSTANCE
             .get_or_init(|| async {
-                let config = aws_config::defaults(BehaviorVersion::latest())
-                    .endpoint_url(format!("https://{}.r2.cloudflarestorage.com", CONFIG.r2.account_id))
-                    .credentials_provider(aws_sdk_s3::config::Credentials::new(
-                        CONFIG.r2.access_key_id.to_owned(),
-                        CONFIG.r2.access_key_secret.to_owned(),
-                        None,
-                        None,
-                        "R2",
-                    ))
-                    .region("auto")
-                    .load()
-                    .await;
-                let s3_client = aws_sdk_s3::Client::new(&config);
                 Self {
-                    s3_client,
-                    bucket_name: CONFIG.r2.bucket_name.as_str(),
-                    prefix: CONFIG.r2.prefix.as_str(),
-                    s3_domain: CONFIG.r2.s3_domain.as_str(),
+                    store: media_store().await,
+                    manifest: MediaManifest::load().await,
                 }
             })
             .await
     }
 
+    pub fn store(&self) -> &dyn MediaStore {
+        self.store.as_ref()
+    }
+
+    /// 媒体文件被删除后清除上传清单中的记录
+    pub async fn forget_media(&self, urls: &HashSet<String>) -> Result<()> {
+        self.manifest.remove_urls(urls);
+        self.manifest.bump_gc_generation().await?;
+        self.manifest.save().await
+    }
+
     pub async fn process_file(&self, path: &Path) -> Result<Markdown> {
+        Ok(self.process(path).await?.0)
+    }
+
+    /// 处理监听目录中的文件，并记录文件与引用的本地媒体的状态，供下次启动时跳过未变化的文件
+    pub async fn process_source(&self, path: &Path) -> Result<Markdown> {
+        let stamp = FileStamp::from(&tokio::fs::metadata(path).await?);
+        let (markdown, local_media) = self.process(path).await?;
+        self.manifest.insert_source(path, stamp, markdown.slug(), local_media);
+        Ok(markdown)
+    }
+
+    /// 文件与引用的本地媒体均未变化时返回上次处理得到的 slug
+    pub async fn unchanged_source(&self, path: &Path) -> Option<String> {
+        self.manifest.unchanged_source(path).await
+    }
+
+    /// 文件处理结果未能写入数据库时调用，下次启动时重新处理
+    pub async fn forget_source(&self, slug: &str) -> Result<()> {
+        self.manifest.remove_source(slug);
+        self.manifest.save().await
+    }
+
+    pub async fn save_manifest(&self) -> Result<()> {
+        self.manifest.save().await
+    }
+
+    async fn process(&self, path: &Path) -> Result<(Markdown, Vec<(PathBuf, FileStamp)>)> {
         info!("Processing markdown file: {:?}", path);
         let mut markdown = Markdown::from_file(path, CONFIG.source_lang).await?;
-        if !markdown.should_publish() {
-            bail!("Markdown file is marked as draft, skipping: {:?}", path);
-        }
+        // 草稿的媒体同样会上传到公开的存储以便预览显示，发布前即可通过其地址访问
         let media_resources = markdown.extract_resources()?;
         if media_resources.is_empty() {
             debug!("No media resources found in markdown");
-            return Ok(markdown);
+            return Ok((markdown, Vec::new()));
         }
         let markdown_dir = path.parent().context("Failed to get markdown file directory")?;
+        // 在上传前记录本地媒体的状态，上传期间的修改会在下次启动时重新处理
+        let mut local_media = Vec::new();
+        for media in &media_resources {
+            if let Some(local_path) = self.local_media_path(media.url(), markdown_dir) {
+                let metadata = tokio::fs::metadata(&local_path)
+                    .await
+                    .with_context(|| format!("Failed to read local media file: {:?}", local_path))?;
+                local_media.push((local_path, FileStamp::from(&metadata)));
+            }
+        }
         let uploaded_media = self
             .upload_medias(markdown.slug(), media_resources, markdown_dir)
             .await?;
         markdown.update_by_uploaded_resource(uploaded_media)?;
-        Ok(markdown)
+        Ok((markdown, local_media))
+    }
+
+    /// 需要从本地读取的媒体文件，已上传的地址、失效链接与远程地址返回 None
+    fn local_media_path(&self, media_url: &str, base_dir: &Path) -> Option<PathBuf> {
+        if self.store.contains_url(media_url)
+            || media_url.starts_with("$dead_link")
+            || media_url.starts_with("http://")
+            || media_url.starts_with("https://")
+        {
+            return None;
+        }
+        let media_path = Path::new(media_url);
+        Some(if media_path.is_absolute() {
+            media_path.to_path_buf()
+        } else {
+            base_dir.join(media_path)
+        })
     }
 
     pub async fn upload_medias(
@@ -81,6 +154,7 @@ impl MarkdownProcessor {
         medias: Vec<MediaResource>,
         base_dir: &Path,
     ) -> Result<Vec<UploadedMedia>> {
+        self.manifest.refresh_gc_generation().await;
         let semaphore = Semaphore::new(8);
         let tasks = medias
             .into_iter()
@@ -89,23 +163,54 @@ impl MarkdownProcessor {
                 self.upload_media(slug, media, base_dir).await
             })
             .collect::<FuturesUnordered<_>>();
-        tasks
-            .try_collect::<Vec<Option<UploadedMedia>>>()
-            .await
-            .map(|results| results.into_iter().flatten().collect())
+        let results = tasks.try_collect::<Vec<Option<UploadedMedia>>>().await;
+        if let Err(e) = self.manifest.save().await {
+            warn!("Failed to save media manifest: {}", e);
+        }
+        Ok(results?.into_iter().flatten().collect())
     }
 
     async fn upload_media(&self, slug: &str, media: MediaResource, base_dir: &Path) -> Result<Option<UploadedMedia>> {
         let media_url = media.url();
-        if media_url.starts_with(self.s3_domain) {
-            debug!("Media already uploaded to S3, skipping: {}", media_url);
+        if self.store.contains_url(media_url) {
+            debug!("Media already uploaded, skipping: {}", media_url);
             return Ok(None);
         }
         if media_url.starts_with("$dead_link") {
             debug!("Media is a dead link, skipping: {}", media_url);
             return Ok(None);
         }
-        let (data, ext) = if media_url.starts_with("http://") || media_url.starts_with("https://") {
+        let local_path = self.local_media_path(media_url, base_dir);
+        let (source, stamp) = match &local_path {
+            Some(local_path) => {
+                let metadata = tokio::fs::metadata(local_path)
+                    .await
+                    .with_context(|| format!("Failed to read local media file: {:?}", local_path))?;
+                (
+                    local_path.to_string_lossy().into_owned(),
+                    Some(FileStamp::from(&metadata)),
+                )
+            }
+            None => (media_url.to_owned(), None),
+        };
+        if let Some(media) = self.manifest_hit(slug, &source, stamp).await {
+            debug!("Media found in manifest, skipping upload: {}", media_url);
+            return Ok(Some(UploadedMedia {
+                original_url: media_url.to_owned(),
+                media,
+            }));
+        }
+        let (data, ext) = if let Some(local_path) = local_path {
+            let data = tokio::fs::read(&local_path)
+                .await
+                .with_context(|| format!("Failed to read local media file: {:?}", local_path))?;
+            let ext = local_path
+                .extension()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_lowercase())
+                .unwrap_or("bin".to_owned());
+            (data, ext)
+        } else {
             static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
                 reqwest::Client::builder()
                     .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:141.0) Gecko/20100101 Firefox/141.0")
@@ -122,48 +227,220 @@ impl MarkdownProcessor {
                 .copied()
                 .unwrap_or("bin");
             let data = resp.bytes().await?.to_vec();
-            Result::<_, anyhow::Error>::Ok((data, ext.to_lowercase()))
+            (data, ext.to_lowercase())
+        };
+        let media = self.upload_data(slug, data, &ext).await?;
+        self.manifest.insert(slug, &source, stamp, media.clone());
+        Ok(Some(UploadedMedia {
+            original_url: media_url.to_owned(),
+            media,
+        }))
+    }
+
+    /// 存储配置变化后清单中的地址不再有效，清理媒体后早于清理的记录需要确认文件仍然存在
+    async fn manifest_hit(&self, slug: &str, source: &str, stamp: Option<FileStamp>) -> Option<StoredMedia> {
+        let (media, verified) = self.manifest.get(slug, source, stamp)?;
+        let key = self.store.key_of_url(&media.url)?;
+        if !verified {
+            match self.store.exists(key).await {
+                Ok(true) => self.manifest.mark_verified(slug, source),
+                Ok(false) => return None,
+                Err(e) => {
+                    warn!("Failed to check media in store {}: {}", key, e);
+                    return None;
+                }
+            }
+        }
+        Some(media)
+    }
+
+    /// 以内容哈希作为文件名上传媒体，图片会转换为 WebP 并生成不同宽度的版本，已存在的文件不会重复上传
+    pub async fn upload_data(&self, slug: &str, data: Vec<u8>, ext: &str) -> Result<StoredMedia> {
+        let hash = Self::hash_binary_data(&data);
+        let image_ext = ["jpg", "jpeg", "png", "webp", "heic", "heif", "tif", "tiff"].contains(&ext);
+        let (data, image) = if image_ext {
+            let (data, image) = spawn_blocking(move || {
+                let image = DecodedImage::decode(&data);
+                (data, image)
+            })
+            .await?;
+            let image = image
+                .inspect_err(|e| {
+                    warn!(
+                        "Failed to decode image, uploading original data without metadata: {}",
+                        e
+                    )
+                })
+                .ok()
+                .map(Arc::new);
+            (data, image)
         } else {
-            let media_path = Path::new(&media_url);
-            let local_path = if media_path.is_absolute() {
-                media_path.to_path_buf()
-            } else {
-                base_dir.join(media_path)
-            };
-            let data = tokio::fs::read(&local_path)
+            (data, None)
+        };
+        let webp_data = match &image {
+            Some(image) => Self::encode_webp(image.clone(), None)
                 .await
-                .with_context(|| format!("Failed to read local media file: {:?}", local_path))?;
-            let ext = local_path
-                .extension()
-                .and_then(|s| s.to_str())
-                .map(|s| s.to_lowercase())
-                .unwrap_or("bin".to_owned());
-            Ok((data, ext))
-        }?;
-        let hash = Self::hash_binary_data(&data);
-        let (data, ext) = if ["jpg", "jpeg", "png", "webp"].contains(&ext.as_str()) {
-            if let Ok(webp_data) = Self::convert_to_webp(&data).await {
-                (webp_data, "webp".to_owned())
-            } else {
-                warn!("Failed to convert image to webp, using original data");
-                (data, ext)
+                .inspect_err(|e| {
+                    warn!(
+                        "Failed to convert image to webp, uploading original data without metadata: {}",
+                        e
+                    )
+                })
+                .ok(),
+            None => None,
+        };
+        let stripped;
+        let (stored_data, ext) = match &webp_data {
+            Some(webp_data) => (webp_data.as_slice(), "webp"),
+            // 无法重新编码的图片原样上传，但需要去除其中可能包含位置信息的元数据
+            None if image_ext => {
+                stripped = strip_metadata(&data, ext).context("Failed to strip image metadata, refusing to upload")?;
+                (stripped.as_slice(), ext)
             }
-        } else {
-            (data, ext)
+            None => (data.as_slice(), ext),
         };
-        let key = format!("{}/{}/{}.{}", self.prefix, slug, hash, ext);
-        if self.file_exists(&key).await? {
-            debug!("File already exists in S3, skipping upload: {}", &key);
-            Ok(Some(UploadedMedia {
-                original_url: media_url.to_owned(),
-                new_url: format!("{}/{}", self.s3_domain, key),
-            }))
+        let size = imagesize::blob_size(stored_data)
+            .ok()
+            .map(|size| (size.width, size.height));
+        let key = format!("{}/{}.{}", slug, hash, ext);
+        if self.store.exists(&key).await? {
+            debug!("File already exists, skipping upload: {}", &key);
         } else {
-            self.upload_file(&key, data).await?;
-            Ok(Some(UploadedMedia {
-                original_url: media_url.to_owned(),
-                new_url: format!("{}/{}", self.s3_domain, key),
-            }))
+            self.store.put(&key, stored_data.to_vec()).await?;
+        }
+        let (video, poster) = if VIDEO_EXTENSIONS.contains(&ext) {
+            self.process_video(slug, &hash, &data, ext).await?
+        } else {
+            (None, None)
+        };
+        let size = size.or(video
+            .as_ref()
+            .map(|video| (video.width as usize, video.height as usize)));
+        let (placeholder, variants) = match image {
+            Some(image) if webp_data.is_some() => {
+                let placeholder = spawn_blocking({
+                    let image = image.clone();
+                    move || image.placeholder()
+                })
+                .await?
+                .inspect_err(|e| warn!("Failed to generate placeholder: {}", e))
+                .ok()
+                .flatten();
+                (placeholder, self.upload_variants(slug, &hash, image, &key).await?)
+            }
+            _ => (None, Vec::new()),
+        };
+        Ok(StoredMedia {
+            url: self.store.public_url(&key),
+            width: size.map(|s| s.0),
+            height: size.map(|s| s.1),
+            placeholder,
+            variants,
+            hash,
+            poster,
+            duration: video
+                .and_then(|video| video.duration)
+                .map(|duration| (duration * 1000.0).round() as u32),
+        })
+    }
+
+    /// 读取视频的宽高与时长，并截取一帧作为封面上传到 {slug}/{hash}.webp；缺少 ffprobe、ffmpeg 时跳过
+    async fn process_video(
+        &self,
+        slug: &str,
+        hash: &str,
+        data: &[u8],
+        ext: &str,
+    ) -> Result<(Option<VideoInfo>, Option<String>)> {
+        // mp4 的索引可能位于文件末尾，外部工具需要可以随机读取的文件
+        let file = tempfile::Builder::new().suffix(&format!(".{}", ext)).tempfile()?;
+        tokio::fs::write(file.path(), data).await?;
+        let video = match VideoInfo::probe(file.path()).await {
+            Ok(video) => video,
+            Err(e) => {
+                warn!("Failed to probe video, skipping poster: {}", e);
+                return Ok((None, None));
+            }
+        };
+        let key = format!("{}/{}.webp", slug, hash);
+        let poster = async {
+            let frame = video.poster(file.path()).await?;
+            let image = spawn_blocking(move || DecodedImage::decode(&frame)).await??;
+            Self::encode_webp(Arc::new(image), None).await
+        };
+        let poster = self
+            .upload_variant(&key, poster)
+            .await?
+            .then(|| self.store.public_url(&key));
+        Ok((Some(video), poster))
+    }
+
+    /// 按配置的宽度生成缩小的 WebP 与可选的 AVIF 版本，原尺寸的 WebP 同样记录在内
+    async fn upload_variants(
+        &self,
+        slug: &str,
+        hash: &str,
+        image: Arc<DecodedImage>,
+        key: &str,
+    ) -> Result<Vec<MediaVariant>> {
+        let config = &CONFIG.responsive_images;
+        let width = image.width();
+        let widths = config
+            .widths
+            .iter()
+            .copied()
+            .filter(|w| *w < width)
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>();
+        let mut variants = Vec::new();
+        for &w in &widths {
+            let key = format!("{}/{}-{}w.webp", slug, hash, w);
+            if self
+                .upload_variant(&key, Self::encode_webp(image.clone(), Some(w)))
+                .await?
+            {
+                variants.push(self.variant(&key, w, "image/webp"));
+            }
+        }
+        variants.push(self.variant(key, width, "image/webp"));
+        if config.avif {
+            for w in widths.into_iter().chain([width]) {
+                let key = format!("{}/{}-{}w.avif", slug, hash, w);
+                if self
+                    .upload_variant(&key, Self::encode_avif(image.clone(), (w < width).then_some(w)))
+                    .await?
+                {
+                    variants.push(self.variant(&key, w, "image/avif"));
+                }
+            }
+        }
+        Ok(variants)
+    }
+
+    /// 上传单个版本，转换失败时跳过该版本
+    async fn upload_variant(&self, key: &str, convert: impl Future<Output = Result<Vec<u8>>>) -> Result<bool> {
+        if self.store.exists(key).await? {
+            debug!("File already exists, skipping upload: {}", key);
+            return Ok(true);
+        }
+        match convert.await {
+            Ok(data) => {
+                self.store.put(key, data).await?;
+                Ok(true)
+            }
+            Err(e) => {
+                warn!("Failed to generate image variant {}: {}", key, e);
+                Ok(false)
+            }
+        }
+    }
+
+    fn variant(&self, key: &str, width: u32, mime_type: &str) -> MediaVariant {
+        MediaVariant {
+            url: self.store.public_url(key),
+            width,
+            mime_type: mime_type.to_owned(),
         }
     }
 
@@ -173,85 +450,54 @@ impl MarkdownProcessor {
         hasher.finish().to_string()
     }
 
-    async fn convert_to_webp(data: &[u8]) -> Result<Vec<u8>> {
-        let mut command = tokio::process::Command::new("cwebp")
-            .args([
-                "-sharp_yuv",
-                "-mt",
-                "-q",
-                "80",
-                "-metadata",
-                "all",
-                "-o",
-                "-",
-                "--",
-                "-",
-            ])
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::null())
-            .spawn()?;
-        if let Some(mut stdin) = command.stdin.take() {
-            stdin.write_all(data).await?;
-        }
-        let output = command.wait_with_output().await?;
-        if !output.status.success() {
-            bail!("cwebp failed with status: {}", output.status);
-        }
-        Ok(output.stdout)
+    /// 编码为 WebP，指定宽度时按比例缩放
+    async fn encode_webp(image: Arc<DecodedImage>, width: Option<u32>) -> Result<Vec<u8>> {
+        spawn_blocking(move || match width {
+            Some(w) => image.resize(w).to_webp(),
+            None => image.to_webp(),
+        })
+        .await?
     }
 
-    async fn file_exists(&self, key: &str) -> Result<bool> {
-        match self
-            .s3_client
-            .head_object()
-            .bucket(self.bucket_name)
-            .key(key)
-            .send()
-            .await
-        {
-            Ok(_) => Ok(true),
-            Err(SdkError::ServiceError(err)) if err.err().is_not_found() => Ok(false),
-            Err(e) => Err(e.into()),
-        }
+    async fn encode_avif(image: Arc<DecodedImage>, width: Option<u32>) -> Result<Vec<u8>> {
+        spawn_blocking(move || match width {
+            Some(w) => image.resize(w).to_avif(),
+            None => image.to_avif(),
+        })
+        .await?
     }
+}
 
-    async fn upload_file(&self, key: &str, content: Vec<u8>) -> Result<()> {
-        let content_type = match key.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).as_deref() {
-            Some("jpg") | Some("jpeg") => "image/jpeg",
-            Some("png") => "image/png",
-            Some("gif") => "image/gif",
-            Some("webp") => "image/webp",
-            Some("svg") => "image/svg+xml",
-            Some("ico") => "image/x-icon",
-            Some("mp4") => "video/mp4",
-            Some("webm") => "video/webm",
-            Some("mov") => "video/quicktime",
-            Some("woff2") => "font/woff2",
-            Some("css") => "text/css; charset=utf-8",
-            Some("js") => "application/javascript; charset=utf-8",
-            Some("html") => "text/html; charset=utf-8",
-            _ => "application/octet-stream",
-        };
-        self.s3_client
-            .put_object()
-            .bucket(self.bucket_name)
-            .key(key)
-            .body(content.into())
-            .content_type(content_type)
-            .send()
-            .await?;
-        Ok(())
+/// 运行外部工具，将数据写入标准输入并返回标准输出
+pub(crate) async fn run_tool(program: &str, args: &[&str], data: &[u8]) -> Result<Vec<u8>> {
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let mut stdin = child.stdin.take().context("Failed to open stdin")?;
+    // 同时写入输入与读取输出，避免输出填满管道后互相等待
+    let write = async move { stdin.write_all(data).await };
+    let (written, output) = tokio::join!(write, child.wait_with_output());
+    let output = output?;
+    if !output.status.success() {
+        bail!("{} failed with status: {}", program, output.status);
     }
+    written?;
+    Ok(output.stdout)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
     use tokio_stream::StreamExt;
 
     use super::*;
-    use std::fs;
-    use std::path::PathBuf;
 
     #[tokio::test]
     async fn test_upload_directory() -> Result<()> {
@@ -267,13 +513,12 @@ mod tests {
                 let file_path = entry.path();
                 let file_name = entry.file_name().to_string_lossy().to_string();
                 let key = format!("icon/{}", file_name);
-                let processor = processor;
                 let semaphore_ref = &semaphore;
                 tasks.push(async move {
                     let _permit = semaphore_ref.acquire().await?;
-                    match processor.upload_file(&key, tokio::fs::read(&file_path).await?).await {
+                    match processor.store().put(&key, tokio::fs::read(&file_path).await?).await {
                         Ok(_) => {
-                            println!("Uploaded: {}/{}", processor.s3_domain, key);
+                            println!("Uploaded: {}", processor.store().public_url(&key));
                             Ok(())
                         }
                         Err(e) => {
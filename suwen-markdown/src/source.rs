@@ -0,0 +1,52 @@
+use anyhow::Result;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::error;
+
+use crate::Markdown;
+
+#[derive(Debug)]
+pub enum MarkdownChange {
+    Upsert(Markdown),
+    /// 草稿：取消发布已有的同名文章（保留评论与点赞），并渲染到预览存储
+    Unpublished(Markdown),
+    Deleted(String),
+    Renamed(String, String),
+    SyncExisting(Vec<String>),
+}
+
+impl From<Markdown> for MarkdownChange {
+    fn from(markdown: Markdown) -> Self {
+        if markdown.should_publish() {
+            MarkdownChange::Upsert(markdown)
+        } else {
+            MarkdownChange::Unpublished(markdown)
+        }
+    }
+}
+
+/// 内容来源，如本地目录、git 仓库等，以变更流的形式产出 markdown 的增删改
+pub trait ContentSource: Send {
+    /// 开始同步并返回变更流，来源停止后流随之结束
+    fn changes(self: Box<Self>) -> BoxStream<'static, MarkdownChange>;
+}
+
+/// 在后台运行向 channel 推送变更的来源，并将 channel 转换为变更流
+pub(crate) fn spawn_source<F>(
+    name: &'static str,
+    run: impl FnOnce(mpsc::UnboundedSender<MarkdownChange>) -> F,
+) -> BoxStream<'static, MarkdownChange>
+where
+    F: Future<Output = Result<()>> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let task = run(sender);
+    tokio::spawn(async move {
+        if let Err(e) = task.await {
+            error!("{} error: {}", name, e);
+        }
+    });
+    UnboundedReceiverStream::new(receiver).boxed()
+}
@@ -0,0 +1,342 @@
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::error::SdkError;
+use futures::future::BoxFuture;
+use suwen_config::{CONFIG, MediaStoreConfig, S3Config};
+
+use crate::UPLOAD_DIR;
+
+/// 媒体文件的存储后端，key 为相对路径，如 {slug}/{hash}.webp
+pub trait MediaStore: Send + Sync {
+    fn exists<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<bool>>;
+
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> BoxFuture<'a, Result<()>>;
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// 列出存储中的全部文件
+    fn list(&self) -> BoxFuture<'_, Result<Vec<StoredObject>>>;
+
+    fn public_url(&self, key: &str) -> String;
+
+    /// 判断地址是否指向存储中的文件
+    fn contains_url(&self, url: &str) -> bool {
+        self.key_of_url(url).is_some()
+    }
+
+    /// 从存储中文件的地址解析出 key
+    fn key_of_url<'a>(&self, url: &'a str) -> Option<&'a str> {
+        url.strip_prefix(&self.public_url(""))
+    }
+
+    /// 存储中的文件是否都由本程序上传，否则不能清理其中的孤立文件
+    fn is_dedicated(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub key: String,
+    pub modified: Option<SystemTime>,
+}
+
+/// 按配置创建存储后端
+pub async fn media_store() -> Box<dyn MediaStore> {
+    match &CONFIG.media_store {
+        MediaStoreConfig::R2 => Box::new(
+            S3Store::new(&S3Config {
+                endpoint: format!("https://{}.r2.cloudflarestorage.com", CONFIG.r2.account_id),
+                region: "auto".to_owned(),
+                bucket_name: CONFIG.r2.bucket_name.clone(),
+                access_key_id: CONFIG.r2.access_key_id.clone(),
+                access_key_secret: CONFIG.r2.access_key_secret.clone(),
+                prefix: CONFIG.r2.prefix.clone(),
+                path_style: false,
+                public_url: CONFIG.r2.s3_domain.clone(),
+            })
+            .await,
+        ),
+        MediaStoreConfig::S3(config) => Box::new(S3Store::new(config).await),
+        MediaStoreConfig::Local(config) => Box::new(LocalStore {
+            root: UPLOAD_DIR.clone(),
+            public_url: match &config.public_url {
+                Some(public_url) => public_url.trim_end_matches('/').to_owned(),
+                None => format!("{}/uploads", CONFIG.host_url.trim_end_matches('/')),
+            },
+        }),
+    }
+}
+
+pub struct S3Store {
+    client: aws_sdk_s3::Client,
+    bucket_name: String,
+    prefix: String,
+    public_url: String,
+}
+
+impl S3Store {
+    pub async fn new(config: &S3Config) -> Self {
+        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
+            .endpoint_url(&config.endpoint)
+            .credentials_provider(aws_sdk_s3::config::Credentials::new(
+                config.access_key_id.to_owned(),
+                config.access_key_secret.to_owned(),
+                None,
+                None,
+                "suwen",
+            ))
+            .region(aws_config::Region::new(config.region.clone()))
+            .load()
+            .await;
+        let s3_config = aws_sdk_s3::config::Builder::from(&sdk_config)
+            .force_path_style(config.path_style)
+            .build();
+        Self {
+            client: aws_sdk_s3::Client::from_conf(s3_config),
+            bucket_name: config.bucket_name.clone(),
+            prefix: config.prefix.trim_matches('/').to_owned(),
+            public_url: config.public_url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}/{}", self.prefix, key)
+        }
+    }
+}
+
+impl MediaStore for S3Store {
+    fn exists<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(async move {
+            match self
+                .client
+                .head_object()
+                .bucket(&self.bucket_name)
+                .key(self.object_key(key))
+                .send()
+                .await
+            {
+                Ok(_) => Ok(true),
+                Err(SdkError::ServiceError(err)) if err.err().is_not_found() => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.client
+                .put_object()
+                .bucket(&self.bucket_name)
+                .key(self.object_key(key))
+                .body(data.into())
+                .content_type(content_type(key))
+                .send()
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.client
+                .delete_object()
+                .bucket(&self.bucket_name)
+                .key(self.object_key(key))
+                .send()
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<StoredObject>>> {
+        Box::pin(async move {
+            let prefix = self.object_key("");
+            let mut pages = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket_name)
+                .prefix(&prefix)
+                .into_paginator()
+                .send();
+            let mut objects = Vec::new();
+            while let Some(page) = pages.try_next().await? {
+                for object in page.contents() {
+                    let Some(key) = object.key().and_then(|key| key.strip_prefix(&prefix)) else {
+                        continue;
+                    };
+                    objects.push(StoredObject {
+                        key: key.to_owned(),
+                        modified: object.last_modified().and_then(|t| SystemTime::try_from(*t).ok()),
+                    });
+                }
+            }
+            Ok(objects)
+        })
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, self.object_key(key))
+    }
+
+    /// 未配置前缀时 bucket 中可能存放着其它程序的文件
+    fn is_dedicated(&self) -> bool {
+        !self.prefix.is_empty()
+    }
+}
+
+pub struct LocalStore {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStore {
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        Ok(self.root.join(safe_relative_path(key)?))
+    }
+}
+
+impl MediaStore for LocalStore {
+    fn exists<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(async move { Ok(tokio::fs::try_exists(self.path(key)?).await?) })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let path = self.path(key)?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, data).await?;
+            Ok(())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { Ok(tokio::fs::remove_file(self.path(key)?).await?) })
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<StoredObject>>> {
+        Box::pin(async move {
+            let mut objects = Vec::new();
+            let mut dirs = vec![self.root.clone()];
+            while let Some(dir) = dirs.pop() {
+                let mut entries = tokio::fs::read_dir(&dir).await?;
+                while let Some(entry) = entries.next_entry().await? {
+                    let metadata = entry.metadata().await?;
+                    if metadata.is_dir() {
+                        dirs.push(entry.path());
+                    } else if let Ok(key) = entry.path().strip_prefix(&self.root) {
+                        objects.push(StoredObject {
+                            key: key.to_string_lossy().replace('\\', "/"),
+                            modified: metadata.modified().ok(),
+                        });
+                    }
+                }
+            }
+            Ok(objects)
+        })
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
+
+/// 校验相对路径，拒绝绝对路径与 ..，避免访问存储目录之外的文件
+///
+/// 反斜杠在 Windows 上是路径分隔符，一并拒绝以保证各平台的行为一致
+pub fn safe_relative_path(key: &str) -> Result<&Path> {
+    let path = Path::new(key);
+    if key.is_empty() || key.contains('\\') || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        bail!("Invalid media key: {}", key);
+    }
+    Ok(path)
+}
+
+fn content_type(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).as_deref() {
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("svg") => "image/svg+xml",
+        Some("ico") => "image/x-icon",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
+        Some("woff2") => "font/woff2",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "application/javascript; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_relative_path() {
+        assert_eq!(
+            safe_relative_path("slug/image.webp").unwrap(),
+            Path::new("slug/image.webp")
+        );
+        for key in [
+            "",
+            "../secret",
+            "slug/../../secret",
+            "/etc/passwd",
+            "./slug/image.webp",
+            "..\\secret",
+            "slug\\image.webp",
+        ] {
+            assert!(safe_relative_path(key).is_err(), "{key}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalStore {
+            root: dir.path().to_path_buf(),
+            public_url: "https://example.com/uploads".to_owned(),
+        };
+        assert!(!store.exists("slug/a.webp").await.unwrap());
+        store.put("slug/a.webp", b"a".to_vec()).await.unwrap();
+        store.put("slug/nested/b.png", b"b".to_vec()).await.unwrap();
+        assert!(store.exists("slug/a.webp").await.unwrap());
+        assert_eq!(std::fs::read(dir.path().join("slug/a.webp")).unwrap(), b"a");
+
+        let mut keys = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, ["slug/a.webp", "slug/nested/b.png"]);
+
+        let url = store.public_url("slug/a.webp");
+        assert_eq!(url, "https://example.com/uploads/slug/a.webp");
+        assert!(store.contains_url(&url));
+        assert!(!store.contains_url("https://example.com/other/a.webp"));
+
+        store.delete("slug/a.webp").await.unwrap();
+        assert!(!store.exists("slug/a.webp").await.unwrap());
+        // 存储目录之外的路径一律拒绝
+        assert!(store.put("../escape.txt", Vec::new()).await.is_err());
+        assert!(store.exists("/etc/passwd").await.is_err());
+        assert!(!dir.path().parent().unwrap().join("escape.txt").exists());
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, ensure};
+use serde::Deserialize;
+
+use crate::processor::run_tool;
+
+/// 截取封面的时间点，视频较短时取中间位置
+const POSTER_SECONDS: f64 = 1.0;
+
+/// 视频的显示尺寸与时长
+pub(crate) struct VideoInfo {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) duration: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    width: Option<u32>,
+    height: Option<u32>,
+    #[serde(default)]
+    side_data_list: Vec<ProbeSideData>,
+    #[serde(default)]
+    tags: ProbeTags,
+}
+
+#[derive(Deserialize)]
+struct ProbeSideData {
+    rotation: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+struct ProbeTags {
+    /// 旧版本 ffprobe 将旋转角度写在标签中
+    rotate: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+}
+
+impl VideoInfo {
+    /// 使用 ffprobe 读取第一条视频流，手机拍摄的竖屏视频会记录旋转角度，此时交换宽高
+    pub(crate) async fn probe(path: &Path) -> Result<Self> {
+        let path = path.to_string_lossy();
+        let output = run_tool(
+            "ffprobe",
+            &[
+                "-v",
+                "error",
+                "-select_streams",
+                "v:0",
+                "-show_entries",
+                "stream=width,height:stream_tags=rotate:stream_side_data=rotation:format=duration",
+                "-of",
+                "json",
+                &path,
+            ],
+            &[],
+        )
+        .await?;
+        Self::parse(&output)
+    }
+
+    /// 解析 ffprobe 输出的 JSON
+    fn parse(output: &[u8]) -> Result<Self> {
+        let output: ProbeOutput = serde_json::from_slice(output)?;
+        let stream = output.streams.into_iter().next().context("No video stream found")?;
+        let (width, height) = stream.width.zip(stream.height).context("Missing video dimensions")?;
+        let rotation = stream
+            .side_data_list
+            .iter()
+            .find_map(|side_data| side_data.rotation)
+            .or_else(|| stream.tags.rotate.and_then(|rotate| rotate.parse().ok()))
+            .unwrap_or_default();
+        let (width, height) = if rotation.rem_euclid(180) == 90 {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let duration = output
+            .format
+            .and_then(|format| format.duration)
+            .and_then(|duration| duration.parse::<f64>().ok())
+            .filter(|duration| duration.is_finite() && *duration >= 0.0);
+        Ok(Self {
+            width,
+            height,
+            duration,
+        })
+    }
+
+    /// 使用 ffmpeg 截取一帧作为封面，返回 PNG 数据
+    pub(crate) async fn poster(&self, path: &Path) -> Result<Vec<u8>> {
+        let seconds = self.duration.map_or(0.0, |duration| POSTER_SECONDS.min(duration / 2.0));
+        let seconds = format!("{:.3}", seconds);
+        let path = path.to_string_lossy();
+        let frame = run_tool(
+            "ffmpeg",
+            &[
+                "-nostdin",
+                "-v",
+                "error",
+                "-ss",
+                &seconds,
+                "-i",
+                &path,
+                "-frames:v",
+                "1",
+                "-f",
+                "image2pipe",
+                "-c:v",
+                "png",
+                "-",
+            ],
+            &[],
+        )
+        .await?;
+        ensure!(!frame.is_empty(), "ffmpeg produced no frame");
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<(u32, u32, Option<f64>)> {
+        let info = VideoInfo::parse(json.as_bytes())?;
+        Ok((info.width, info.height, info.duration))
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        let side_data = r#"{"streams": [{"width": 1920, "height": 1080, "side_data_list": [{"rotation": -90}]}]}"#;
+        assert_eq!(parse(side_data).unwrap(), (1080, 1920, None));
+        let tags = r#"{"streams": [{"width": 1920, "height": 1080, "tags": {"rotate": "270"}}]}"#;
+        assert_eq!(parse(tags).unwrap(), (1080, 1920, None));
+        let upside_down = r#"{"streams": [{"width": 1920, "height": 1080, "side_data_list": [{"rotation": 180}]}]}"#;
+        assert_eq!(parse(upside_down).unwrap(), (1920, 1080, None));
+        // 其它 side data 没有旋转角度
+        let other = r#"{"streams": [{"width": 640, "height": 480, "side_data_list": [{}, {"rotation": 90}]}]}"#;
+        assert_eq!(parse(other).unwrap(), (480, 640, None));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let probe = |duration: &str| {
+            let json =
+                format!(r#"{{"streams": [{{"width": 640, "height": 480}}], "format": {{"duration": "{duration}"}}}}"#);
+            parse(&json).unwrap().2
+        };
+        assert_eq!(probe("12.345000"), Some(12.345));
+        assert_eq!(probe("N/A"), None);
+        assert_eq!(probe("-1"), None);
+        assert_eq!(probe("inf"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse(r#"{"streams": []}"#).is_err());
+        assert!(parse(r#"{"streams": [{"width": 640}]}"#).is_err());
+        assert!(parse("not json").is_err());
+    }
+}
@@ -1,36 +1,75 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::Result;
 use dashmap::DashMap;
-use notify::event::{CreateKind, DataChange, ModifyKind, RenameMode};
+use futures::stream::BoxStream;
+use notify::event::{CreateKind, ModifyKind, RenameMode};
 use notify::{EventKind, RecursiveMode};
 use notify_debouncer_full::{DebouncedEvent, new_debouncer};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
-use crate::{Markdown, MarkdownProcessor};
+use crate::files::{is_hidden, is_markdown, markdown_files};
+use crate::source::spawn_source;
+use crate::{ContentSource, Markdown, MarkdownChange, MarkdownProcessor};
 
+/// 监听本地目录中的 markdown 文件
 pub struct MarkdownWatcher {
     watch_path: PathBuf,
-    db_sender: mpsc::UnboundedSender<MarkdownChange>,
+    synced_slugs: HashSet<String>,
 }
 
-#[derive(Debug)]
-pub enum MarkdownChange {
-    Upsert(Markdown),
-    Deleted(String),
-    Renamed(String, String),
-    SyncExisting(Vec<String>),
+impl MarkdownWatcher {
+    pub fn new(watch_path: PathBuf) -> Self {
+        Self {
+            watch_path,
+            synced_slugs: HashSet::new(),
+        }
+    }
+
+    /// 数据库中已有的 slug，启动扫描时这些文章的文件未变化即可跳过处理
+    pub fn with_synced_slugs(mut self, synced_slugs: HashSet<String>) -> Self {
+        self.synced_slugs = synced_slugs;
+        self
+    }
 }
 
-impl MarkdownWatcher {
-    pub fn new(watch_path: PathBuf, db_sender: mpsc::UnboundedSender<MarkdownChange>) -> Self {
-        Self { watch_path, db_sender }
+impl ContentSource for MarkdownWatcher {
+    fn changes(self: Box<Self>) -> BoxStream<'static, MarkdownChange> {
+        spawn_source("Markdown watcher", |sender| {
+            WatchState::new(self.watch_path, self.synced_slugs, sender).start_watching()
+        })
     }
+}
 
-    pub async fn start_watching(self) -> Result<()> {
+struct WatchState {
+    watch_path: PathBuf,
+    synced_slugs: HashSet<String>,
+    db_sender: mpsc::UnboundedSender<MarkdownChange>,
+    // 文件路径与 slug 的双向映射，slug 可能来自 front matter 而非文件名
+    slug_by_path: DashMap<PathBuf, String>,
+    path_by_slug: DashMap<String, PathBuf>,
+}
+
+impl WatchState {
+    fn new(
+        watch_path: PathBuf,
+        synced_slugs: HashSet<String>,
+        db_sender: mpsc::UnboundedSender<MarkdownChange>,
+    ) -> Self {
+        Self {
+            watch_path,
+            synced_slugs,
+            db_sender,
+            slug_by_path: DashMap::new(),
+            path_by_slug: DashMap::new(),
+        }
+    }
+
+    async fn start_watching(self) -> Result<()> {
         let (tx, mut rx) = mpsc::unbounded_channel();
 
         info!("Initial scan of markdown files in {:?}", self.watch_path);
@@ -43,12 +82,15 @@ impl MarkdownWatcher {
                 }
             }
         })?;
-        debouncer.watch(&self.watch_path, RecursiveMode::NonRecursive)?;
+        debouncer.watch(&self.watch_path, RecursiveMode::Recursive)?;
         info!("Started watching markdown files in {:?}", self.watch_path);
         let pending_deletes = DashMap::new();
         while let Some(event) = rx.recv().await {
             debug!("Received file system event: {:?}", event);
             self.handle_event(event, &pending_deletes).await?;
+            if let Err(e) = MarkdownProcessor::get().await.save_manifest().await {
+                warn!("Failed to save media manifest: {}", e);
+            }
         }
         Ok(())
     }
@@ -59,49 +101,59 @@ impl MarkdownWatcher {
         pending_deletes: &DashMap<String, JoinHandle<()>>,
     ) -> Result<()> {
         let event = event.event;
-        if event.paths.iter().any(|p| p.extension().is_none_or(|ext| ext != "md")) {
+        if event.paths.iter().any(|p| is_hidden(&self.watch_path, p)) {
+            return Ok(());
+        }
+        if let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind
+            && event.paths[1].is_dir()
+        {
+            self.handle_dir_renamed(&event.paths[0], &event.paths[1]);
+            return Ok(());
+        }
+        if event.paths.iter().any(|p| !is_markdown(p)) {
             return Ok(());
         }
         match event.kind {
             EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
                 let (old_path, new_path) = (&event.paths[0], &event.paths[1]);
-                if let (Some(old_stem), Some(new_stem)) = (
-                    old_path.file_stem().and_then(|s| s.to_str()),
-                    new_path.file_stem().and_then(|s| s.to_str()),
-                ) {
-                    info!("Markdown file renamed from {:?} to {:?}", old_path, new_path);
+                info!("Markdown file renamed from {:?} to {:?}", old_path, new_path);
+                let old_slug = self.release_path(old_path);
+                let Some(markdown) = self.process_file(new_path).await else {
+                    return Ok(());
+                };
+                if let Some(old_slug) = old_slug
+                    && old_slug != markdown.slug()
+                {
                     let _ = self
                         .db_sender
-                        .send(MarkdownChange::Renamed(old_stem.to_string(), new_stem.to_string()));
+                        .send(MarkdownChange::Renamed(old_slug, markdown.slug().to_owned()));
                 }
+                let _ = self.db_sender.send(MarkdownChange::from(markdown));
             }
-            EventKind::Create(CreateKind::File) | EventKind::Modify(ModifyKind::Data(DataChange::Content)) => {
+            // 移入或移出监听目录的文件只会产生单边的重命名事件，分别视为新建与删除
+            EventKind::Create(CreateKind::File)
+            | EventKind::Modify(ModifyKind::Data(_))
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
                 let path = &event.paths[0];
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str())
-                    && let Some((_, handle)) = pending_deletes.remove(stem)
-                {
-                    info!("Cancel pending delete because of new modification: {}", stem);
+                let Some(markdown) = self.process_file(path).await else {
+                    return Ok(());
+                };
+                if let Some((_, handle)) = pending_deletes.remove(markdown.slug()) {
+                    info!("Cancel pending delete because of new modification: {}", markdown.slug());
                     handle.abort();
                 }
-                match MarkdownProcessor::get().await.process_file(path).await {
-                    Ok(markdown) => {
-                        let _ = self.db_sender.send(MarkdownChange::Upsert(markdown));
-                    }
-                    Err(e) => {
-                        warn!("Failed to process markdown file {:?}: {}", path, e);
-                    }
-                }
+                let _ = self.db_sender.send(MarkdownChange::from(markdown));
             }
-            EventKind::Remove(_) => {
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
                 let path = &event.paths[0];
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    let stem_owned = stem.to_owned();
+                if let Some(slug) = self.release_path(path) {
                     let sender = self.db_sender.clone();
+                    let slug_owned = slug.clone();
                     let handle = tokio::spawn(async move {
                         tokio::time::sleep(Duration::from_secs(10)).await;
-                        let _ = sender.send(MarkdownChange::Deleted(stem_owned));
+                        let _ = sender.send(MarkdownChange::Deleted(slug_owned));
                     });
-                    pending_deletes.insert(stem.to_string(), handle);
+                    pending_deletes.insert(slug, handle);
                 }
             }
             _ => {
@@ -112,25 +164,161 @@ impl MarkdownWatcher {
     }
 
     async fn scan_existing_files(&self) -> Result<()> {
+        let paths = markdown_files(&self.watch_path).await?;
+        let processor = MarkdownProcessor::get().await;
         let mut existing_slugs = Vec::new();
-        let mut entries = tokio::fs::read_dir(&self.watch_path).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "md") {
-                if let Some(slug) = path.file_stem().and_then(|s| s.to_str()) {
-                    existing_slugs.push(slug.to_string());
+        for path in paths {
+            // 文件与引用的本地媒体均未变化且数据库中已有该文章时无需重新处理
+            if let Some(slug) = processor.unchanged_source(&path).await
+                && self.synced_slugs.contains(&slug)
+            {
+                if self.claim(&path, &slug) {
+                    debug!("Markdown file unchanged, skipping: {:?}", path);
+                } else {
+                    existing_slugs.extend(path.file_stem().and_then(|s| s.to_str()).map(str::to_owned));
                 }
-                match MarkdownProcessor::get().await.process_file(&path).await {
-                    Ok(markdown) => {
-                        let _ = self.db_sender.send(MarkdownChange::Upsert(markdown));
-                    }
-                    Err(e) => {
-                        warn!("Failed to process existing markdown file {:?}: {}", path, e);
-                    }
+                continue;
+            }
+            match self.process_file(&path).await {
+                Some(markdown) => {
+                    let _ = self.db_sender.send(MarkdownChange::from(markdown));
                 }
+                // 解析失败时保留以文件名为 slug 的已有文章，避免被同步清理
+                None => existing_slugs.extend(path.file_stem().and_then(|s| s.to_str()).map(str::to_owned)),
             }
         }
+        existing_slugs.extend(self.path_by_slug.iter().map(|entry| entry.key().clone()));
         let _ = self.db_sender.send(MarkdownChange::SyncExisting(existing_slugs));
+        if let Err(e) = processor.save_manifest().await {
+            warn!("Failed to save media manifest: {}", e);
+        }
         Ok(())
     }
+
+    /// 处理 markdown 文件并登记其 slug，slug 已被其它文件占用时跳过该文件
+    async fn process_file(&self, path: &Path) -> Option<Markdown> {
+        let markdown = match MarkdownProcessor::get().await.process_source(path).await {
+            Ok(markdown) => markdown,
+            Err(e) => {
+                warn!("Failed to process markdown file {:?}: {}", path, e);
+                return None;
+            }
+        };
+        self.claim(path, markdown.slug()).then_some(markdown)
+    }
+
+    /// 登记文件的 slug，slug 已被其它仍然存在的文件占用时返回 false
+    fn claim(&self, path: &Path, slug: &str) -> bool {
+        if let Some(owner) = self.path_by_slug.get(slug)
+            && owner.value() != path
+            && owner.exists()
+        {
+            warn!(
+                "Slug collision: {:?} and {:?} both use slug {}, skipping the latter",
+                owner.value(),
+                path,
+                slug
+            );
+            return false;
+        }
+        // 文件的 slug 发生变化（如修改了 front matter）时释放旧 slug，并视为文章改名
+        if let Some(old_slug) = self.slug_by_path.insert(path.to_path_buf(), slug.to_owned())
+            && old_slug != slug
+        {
+            self.path_by_slug.remove(&old_slug);
+            let _ = self.db_sender.send(MarkdownChange::Renamed(old_slug, slug.to_owned()));
+        }
+        self.path_by_slug.insert(slug.to_owned(), path.to_path_buf());
+        true
+    }
+
+    fn release_path(&self, path: &Path) -> Option<String> {
+        let (_, slug) = self.slug_by_path.remove(path)?;
+        self.path_by_slug.remove_if(&slug, |_, owner| owner == path);
+        Some(slug)
+    }
+
+    /// 目录被重命名时文件内容与 slug 均未改变，只需要更新登记的路径
+    fn handle_dir_renamed(&self, old_dir: &Path, new_dir: &Path) {
+        info!("Directory renamed from {:?} to {:?}", old_dir, new_dir);
+        let moved = self
+            .slug_by_path
+            .iter()
+            .filter_map(|entry| {
+                let relative = entry.key().strip_prefix(old_dir).ok()?;
+                Some((entry.key().clone(), new_dir.join(relative)))
+            })
+            .collect::<Vec<_>>();
+        for (old_path, new_path) in moved {
+            if let Some((_, slug)) = self.slug_by_path.remove(&old_path) {
+                self.path_by_slug.insert(slug.clone(), new_path.clone());
+                self.slug_by_path.insert(new_path, slug);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_markdown(path: &Path, slug: &str) {
+        let content = format!("---\ntype: article\nslug: {slug}\ntitle: {slug}\ntags: []\n---\n正文\n");
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_slug_collision() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let state = WatchState::new(dir.path().to_path_buf(), HashSet::new(), tx);
+        let (first, second) = (dir.path().join("first.md"), dir.path().join("second.md"));
+        write_markdown(&first, "watcher-collision");
+        write_markdown(&second, "watcher-collision");
+
+        assert!(state.process_file(&first).await.is_some());
+        // slug 已被仍然存在的文件占用，后处理的文件被跳过
+        assert!(state.process_file(&second).await.is_none());
+        assert_eq!(*state.path_by_slug.get("watcher-collision").unwrap(), first);
+        // 占用者被删除后 slug 可以被接管
+        std::fs::remove_file(&first).unwrap();
+        assert!(state.process_file(&second).await.is_some());
+        assert_eq!(*state.path_by_slug.get("watcher-collision").unwrap(), second);
+
+        // 修改 front matter 中的 slug 视为改名，并释放旧 slug
+        write_markdown(&second, "watcher-collision-renamed");
+        assert!(state.process_file(&second).await.is_some());
+        assert!(!state.path_by_slug.contains_key("watcher-collision"));
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            MarkdownChange::Renamed(old, new) if old == "watcher-collision" && new == "watcher-collision-renamed"
+        ));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_dir_renamed() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let state = WatchState::new(PathBuf::from("/notes"), HashSet::new(), tx);
+        for (path, slug) in [
+            ("/notes/old/a.md", "a"),
+            ("/notes/old/nested/b.md", "b"),
+            ("/notes/older/c.md", "c"),
+        ] {
+            state.slug_by_path.insert(PathBuf::from(path), slug.to_owned());
+            state.path_by_slug.insert(slug.to_owned(), PathBuf::from(path));
+        }
+
+        state.handle_dir_renamed(Path::new("/notes/old"), Path::new("/notes/new"));
+
+        for (path, slug) in [
+            ("/notes/new/a.md", "a"),
+            ("/notes/new/nested/b.md", "b"),
+            ("/notes/older/c.md", "c"),
+        ] {
+            assert_eq!(*state.slug_by_path.get(Path::new(path)).unwrap(), slug);
+            assert_eq!(*state.path_by_slug.get(slug).unwrap(), PathBuf::from(path));
+        }
+        assert_eq!(state.slug_by_path.len(), 3);
+    }
 }
@@ -1,12 +1,40 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20250802_051117_init;
+mod m20261019_090000_content_embedding;
+mod m20261019_110000_series;
+mod m20261019_130000_content_preview;
+mod m20261019_150000_slug_redirect;
+mod m20261019_170000_soft_delete;
+mod m20261019_190000_source_commit;
+mod m20261019_210000_media;
+mod m20261019_220000_media_placeholder;
+mod m20261019_230000_image_alt_text;
+mod m20261019_235000_media_video;
+mod m20261019_235500_imported_comment;
+mod m20261019_235800_unpublished;
+mod m20261019_235900_publish_check;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20250802_051117_init::Migration)]
+        vec![
+            Box::new(m20250802_051117_init::Migration),
+            Box::new(m20261019_090000_content_embedding::Migration),
+            Box::new(m20261019_110000_series::Migration),
+            Box::new(m20261019_130000_content_preview::Migration),
+            Box::new(m20261019_150000_slug_redirect::Migration),
+            Box::new(m20261019_170000_soft_delete::Migration),
+            Box::new(m20261019_190000_source_commit::Migration),
+            Box::new(m20261019_210000_media::Migration),
+            Box::new(m20261019_220000_media_placeholder::Migration),
+            Box::new(m20261019_230000_image_alt_text::Migration),
+            Box::new(m20261019_235000_media_video::Migration),
+            Box::new(m20261019_235500_imported_comment::Migration),
+            Box::new(m20261019_235800_unpublished::Migration),
+            Box::new(m20261019_235900_publish_check::Migration),
+        ]
     }
 }
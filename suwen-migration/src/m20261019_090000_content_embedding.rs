@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContentEmbedding::Table)
+                    .if_not_exists()
+                    .col(integer(ContentEmbedding::ContentMetadataId).primary_key())
+                    .col(text(ContentEmbedding::Model))
+                    .col(text(ContentEmbedding::Vector))
+                    .col(date_time(ContentEmbedding::UpdatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_content_embedding_content_metadata")
+                            .from(ContentEmbedding::Table, ContentEmbedding::ContentMetadataId)
+                            .to(ContentMetadata::Table, ContentMetadata::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_content_embedding__model")
+                    .table(ContentEmbedding::Table)
+                    .col(ContentEmbedding::Model)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContentEmbedding::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContentEmbedding {
+    Table,
+    ContentMetadataId,
+    Model,
+    Vector,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ContentMetadata {
+    Table,
+    Id,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Series::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Series::Id))
+                    .col(text(Series::Name).unique_key())
+                    .col(date_time(Series::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+        // SQLite 不支持通过 ALTER TABLE 添加外键，系列被删除时由应用层负责置空
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentMetadata::Table)
+                    .add_column(integer_null(ContentMetadata::SeriesId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentMetadata::Table)
+                    .add_column(integer_null(ContentMetadata::SeriesOrder))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_content_metadata__series")
+                    .table(ContentMetadata::Table)
+                    .col(ContentMetadata::SeriesId)
+                    .col(ContentMetadata::SeriesOrder)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_content_metadata__series")
+                    .table(ContentMetadata::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentMetadata::Table)
+                    .drop_column(ContentMetadata::SeriesOrder)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentMetadata::Table)
+                    .drop_column(ContentMetadata::SeriesId)
+                    .to_owned(),
+            )
+            .await?;
+        manager.drop_table(Table::drop().table(Series::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Series {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ContentMetadata {
+    Table,
+    SeriesId,
+    SeriesOrder,
+}
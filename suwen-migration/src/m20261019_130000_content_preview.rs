@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 草稿的渲染结果单独存放，不参与任何公开查询
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContentPreview::Table)
+                    .if_not_exists()
+                    .col(text(ContentPreview::Slug).primary_key())
+                    .col(text(ContentPreview::ContentType))
+                    .col(text(ContentPreview::Title))
+                    .col(text_null(ContentPreview::RenderedHtml))
+                    .col(text_null(ContentPreview::Toc))
+                    .col(text(ContentPreview::Tags))
+                    .col(text(ContentPreview::CoverImages))
+                    .col(date_time_null(ContentPreview::PublishedAt))
+                    .col(date_time(ContentPreview::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContentPreview::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContentPreview {
+    Table,
+    Slug,
+    ContentType,
+    Title,
+    RenderedHtml,
+    Toc,
+    Tags,
+    CoverImages,
+    PublishedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SlugRedirect::Table)
+                    .if_not_exists()
+                    .col(text(SlugRedirect::OldSlug).primary_key())
+                    .col(integer(SlugRedirect::ContentMetadataId))
+                    .col(date_time(SlugRedirect::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_slug_redirect_content_metadata")
+                            .from(SlugRedirect::Table, SlugRedirect::ContentMetadataId)
+                            .to(ContentMetadata::Table, ContentMetadata::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_slug_redirect__content_metadata_id")
+                    .table(SlugRedirect::Table)
+                    .col(SlugRedirect::ContentMetadataId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SlugRedirect::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SlugRedirect {
+    Table,
+    OldSlug,
+    ContentMetadataId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ContentMetadata {
+    Table,
+    Id,
+}
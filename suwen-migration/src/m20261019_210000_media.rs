@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 记录已上传媒体的尺寸与各宽度的版本，以地址作为主键
+        manager
+            .create_table(
+                Table::create()
+                    .table(Media::Table)
+                    .if_not_exists()
+                    .col(text(Media::Url).primary_key())
+                    .col(integer_null(Media::Width))
+                    .col(integer_null(Media::Height))
+                    .col(text(Media::Variants))
+                    .col(date_time(Media::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Media::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Media {
+    Table,
+    Url,
+    Width,
+    Height,
+    Variants,
+    UpdatedAt,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Media::Table)
+                    .add_column(text_null(Media::Hash))
+                    .to_owned(),
+            )
+            .await?;
+        // 以图片内容的哈希缓存生成的替代文本，同一张图片只生成一次
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImageAltText::Table)
+                    .if_not_exists()
+                    .col(text(ImageAltText::Hash).primary_key())
+                    .col(text(ImageAltText::AltText))
+                    .col(text(ImageAltText::Model))
+                    .col(date_time(ImageAltText::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ImageAltText::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(Table::alter().table(Media::Table).drop_column(Media::Hash).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Media {
+    Table,
+    Hash,
+}
+
+#[derive(DeriveIden)]
+enum ImageAltText {
+    Table,
+    Hash,
+    AltText,
+    Model,
+    CreatedAt,
+}
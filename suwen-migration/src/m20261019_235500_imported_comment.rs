@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::schema::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 从其它平台导入的评论者没有账号，只保留昵称
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Identity::Table)
+                    .add_column(text_null(Identity::DisplayName))
+                    .to_owned(),
+            )
+            .await?;
+        // 记录评论在原平台的标识，重复导入时跳过
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Comment::Table)
+                    .add_column(text_null(Comment::SourceId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_comment__source_id")
+                    .table(Comment::Table)
+                    .col(Comment::SourceId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_comment__source_id")
+                    .table(Comment::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Comment::Table)
+                    .drop_column(Comment::SourceId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Identity::Table)
+                    .drop_column(Identity::DisplayName)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Identity {
+    Table,
+    DisplayName,
+}
+
+#[derive(DeriveIden)]
+enum Comment {
+    Table,
+    SourceId,
+}
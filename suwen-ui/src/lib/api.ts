@@ -1,3 +1,5 @@
+import { redirect } from '@sveltejs/kit';
+
 import type { ApiResponse } from './type';
 
 export async function rawRequest(
@@ -49,6 +51,24 @@ export async function request<T>(
 	return await extractApiResponse<T>(response);
 }
 
+/**
+ * 旧 slug 会返回 301 与新的 slug，此时跳转到以新 slug 访问的页面
+ */
+export async function requestFollowingSlug<T>(
+	fetch: typeof window.fetch,
+	requestUrl: string,
+	pagePrefix: string
+): Promise<T> {
+	const response = await rawRequest(fetch, requestUrl, { redirect: 'manual' });
+	if (response.status === 301) {
+		const apiResponse: ApiResponse<{ slug: string }> = await response.json();
+		if (apiResponse.data) {
+			redirect(301, `${pagePrefix}/${encodeURIComponent(apiResponse.data.slug)}`);
+		}
+	}
+	return await extractApiResponse<T>(response);
+}
+
 export async function extractApiResponse<T>(response: Response): Promise<T> {
 	const apiResponse: ApiResponse<T> = await response.json();
 	if (apiResponse.statusCode >= 400 || apiResponse.data === undefined) {
@@ -30,7 +30,7 @@ export interface ArticleByList {
 	title: string;
 	intro: string | null;
 	summary: string | null;
-	coverImages: string[];
+	coverImages: CoverImage[];
 	tags: string[];
 	viewCount: number;
 	commentCount: number;
@@ -48,14 +48,49 @@ export interface ArticleBySlug {
 	commentCount: number;
 	likeCount: number;
 	publishedAt: string;
+	series: SeriesNavigation | null;
+}
+
+export interface SeriesLink {
+	slug: string;
+	title: string;
+}
+
+export interface SeriesNavigation {
+	name: string;
+	position: number;
+	total: number;
+	prev: SeriesLink | null;
+	next: SeriesLink | null;
 }
 
 export interface Short {
 	slug: string;
 	title: string;
-	coverImages: string[];
+	coverImages: CoverImage[];
 	content: string;
 	renderedHtml: string | null;
+	publishedAt: string | null;
+}
+
+export interface CoverImage {
+	url: string;
+	width: number | null;
+	height: number | null;
+	placeholder: string | null;
+	variants: MediaVariant[];
+}
+
+export interface MediaVariant {
+	url: string;
+	width: number;
+	mimeType: string;
+}
+
+export interface Page<T> {
+	items: T[];
+	nextCursor: string | null;
+	hasMore: boolean;
 }
 
 export interface TagWithCount {
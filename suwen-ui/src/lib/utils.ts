@@ -1,10 +1,19 @@
 import { clsx, type ClassValue } from 'clsx';
 import { twMerge } from 'tailwind-merge';
+import type { MediaVariant } from './type';
 
 export function cn(...inputs: ClassValue[]) {
 	return twMerge(clsx(inputs));
 }
 
+export function srcset(variants: MediaVariant[] | undefined, mimeType: string) {
+	return (variants ?? [])
+		.filter((variant) => variant.mimeType === mimeType)
+		.sort((a, b) => a.width - b.width)
+		.map((variant) => `${variant.url} ${variant.width}w`)
+		.join(', ');
+}
+
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 export type WithoutChild<T> = T extends { child?: any } ? Omit<T, 'child'> : T;
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
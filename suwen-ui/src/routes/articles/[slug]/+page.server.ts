@@ -1,10 +1,14 @@
-import { request } from '@/api';
+import { request, requestFollowingSlug } from '@/api';
 import type { ArticleBySlug, Comment } from '@/type';
 
 export const load = async ({ fetch, params }) => {
 	const { slug } = params;
-	const [article, comments, likes] = await Promise.all([
-		request<ArticleBySlug>(fetch, `/api/articles/${slug}`),
+	const article = await requestFollowingSlug<ArticleBySlug>(
+		fetch,
+		`/api/articles/${slug}`,
+		'/articles'
+	);
+	const [comments, likes] = await Promise.all([
 		request<Comment[]>(fetch, `/api/articles/${slug}/comments`),
 		request<boolean>(fetch, `/api/articles/${slug}/likes`)
 	]);
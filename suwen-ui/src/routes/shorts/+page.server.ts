@@ -1,10 +1,10 @@
 import { request } from '@/api';
-import type { Short } from '@/type';
+import type { Page, Short } from '@/type';
 
 export const load = async ({ fetch, url }) => {
 	const sort = url.searchParams.get('sort');
-	const shorts = await request<Short[]>(fetch, '/api/shorts' + (sort ? `?sort=${sort}` : ''));
+	const shorts = await request<Page<Short>>(fetch, '/api/shorts' + (sort ? `?sort=${sort}` : ''));
 	return {
-		shorts
+		shorts: shorts.items
 	};
 };
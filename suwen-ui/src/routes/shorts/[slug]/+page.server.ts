@@ -1,9 +1,9 @@
-import { request } from '@/api';
+import { requestFollowingSlug } from '@/api';
 import type { Short } from '@/type';
 
 export const load = async ({ fetch, params }) => {
 	const { slug } = params;
-	const short = await request<Short>(fetch, `/api/shorts/${slug}`);
+	const short = await requestFollowingSlug<Short>(fetch, `/api/shorts/${slug}`, '/shorts');
 	return {
 		short
 	};
@@ -1,10 +1,11 @@
 import { request } from '@/api.js';
+import type { ArticleByList, Page } from '@/type';
 
 export const load = async ({ fetch, params }) => {
 	const tag = params.tag;
-	const articles = await request(fetch, `/api/tags/${tag}/articles`);
+	const articles = await request<Page<ArticleByList>>(fetch, `/api/tags/${tag}/articles`);
 	return {
 		tag,
-		articles
+		articles: articles.items
 	};
 };
@@ -6,13 +6,13 @@ use std::sync::LazyLock;
 
 use anyhow::{Result, bail};
 use axum::Extension;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
 use suwen_api::db;
 use suwen_config::CONFIG;
-use suwen_markdown::MarkdownWatcher;
-use suwen_markdown::importer::XlogImporter;
+use suwen_markdown::importer::{COMMENTS_FILE, ImportedComment, SsgImporter, XlogImporter, import_wordpress};
+use suwen_markdown::{ContentSource, GitSource, MarkdownChange, MarkdownProcessor, MarkdownWatcher};
 use tokio::signal;
-use tokio::sync::mpsc;
 use tracing_subscriber::util::SubscriberInitExt;
 
 static BACKEND_PORT: LazyLock<String> =
@@ -36,6 +36,48 @@ enum Commands {
         #[arg(short = 'i', long)]
         obj_output: Option<PathBuf>,
     },
+    /// 从其它博客平台导入内容，生成 markdown 文件；WordPress 的评论保存在输出目录的 comments.json 中
+    Import {
+        #[arg(short, long, value_enum)]
+        format: ImportFormat,
+        #[arg(short = 's', long)]
+        source: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        #[arg(short = 'i', long)]
+        obj_output: Option<PathBuf>,
+    },
+    /// 将 WordPress 导入时保存的评论写入数据库，需要在导入的文章同步后执行
+    ImportComments {
+        /// 导入时生成的 comments.json
+        #[arg(short = 's', long)]
+        source: PathBuf,
+    },
+    /// 管理存储中的媒体文件
+    Media {
+        #[command(subcommand)]
+        command: MediaCommands,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    Xlog,
+    Hugo,
+    Jekyll,
+    Hexo,
+    /// WordPress 导出的 WXR 文件
+    Wordpress,
+}
+
+#[derive(Subcommand)]
+enum MediaCommands {
+    /// 清理不再被任何内容引用的媒体文件
+    Gc {
+        /// 只列出将被删除的文件
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[tokio::main]
@@ -48,6 +90,16 @@ async fn main() -> Result<()> {
             output,
             obj_output,
         }) => import_xlog_content(source, output, obj_output).await,
+        Some(Commands::Import {
+            format,
+            source,
+            output,
+            obj_output,
+        }) => import_content(format, source, output, obj_output).await,
+        Some(Commands::ImportComments { source }) => import_comments(source).await,
+        Some(Commands::Media {
+            command: MediaCommands::Gc { dry_run },
+        }) => media_gc(dry_run).await,
     }
 }
 
@@ -56,33 +108,34 @@ async fn serve() -> Result<()> {
     let router = suwen_api::router().layer(Extension(sqlite_connection.clone()));
     let bind_address = format!("0.0.0.0:{}", BACKEND_PORT.as_str());
     let listener = tokio::net::TcpListener::bind(&bind_address).await?;
-    let (db_sender, mut db_receiver) = mpsc::unbounded_channel();
-
-    if let Some(markdown_path) = &CONFIG.markdown_path {
-        let watch_path = PathBuf::from(markdown_path);
-        if watch_path.exists() {
-            info!("Starting markdown watcher at {:?}", watch_path);
-            let watcher = MarkdownWatcher::new(watch_path, db_sender);
-            tokio::spawn(async move {
-                if let Err(e) = watcher.start_watching().await {
-                    error!("Markdown watcher error: {}", e);
+
+    if let Some(source) = content_source(&sqlite_connection).await? {
+        let db_conn = sqlite_connection.clone();
+        let mut changes = source.changes();
+        tokio::spawn(async move {
+            while let Some(change) = changes.next().await {
+                let slug = match &change {
+                    MarkdownChange::Upsert(markdown) | MarkdownChange::Unpublished(markdown) => {
+                        Some(markdown.slug().to_owned())
+                    }
+                    _ => None,
+                };
+                if let Err(e) = db::handle_markdown_change(&db_conn, change).await {
+                    error!("Failed to handle markdown change: {}", e);
+                    // 未能写入数据库的文件在下次启动时需要重新处理
+                    if let Some(slug) = slug
+                        && let Err(e) = MarkdownProcessor::get().await.forget_source(&slug).await
+                    {
+                        warn!("Failed to forget markdown source {}: {}", slug, e);
+                    }
                 }
-            });
-        } else {
-            bail!("Markdown path {:?} does not exist", watch_path);
-        }
-    } else {
-        info!("No markdown path configured, skipping markdown watcher");
+            }
+        });
     }
 
-    let db_conn = sqlite_connection.clone();
-    tokio::spawn(async move {
-        while let Some(change) = db_receiver.recv().await {
-            if let Err(e) = db::handle_markdown_change(&db_conn, change).await {
-                error!("Failed to handle markdown change: {}", e);
-            }
-        }
-    });
+    tokio::spawn(suwen_api::run_publish_scheduler(sqlite_connection.clone()));
+    tokio::spawn(suwen_api::run_trash_purger(sqlite_connection.clone()));
+    tokio::spawn(suwen_api::run_media_gc(sqlite_connection.clone()));
 
     let (tx, rx) = tokio::sync::oneshot::channel();
     tokio::spawn(async move {
@@ -108,6 +161,27 @@ async fn serve() -> Result<()> {
     Ok(())
 }
 
+/// 按配置选择内容来源，配置了 git 仓库时优先于本地目录
+async fn content_source(conn: &db::DatabaseConnection) -> Result<Option<Box<dyn ContentSource>>> {
+    if let Some(git_source) = &CONFIG.git_source {
+        info!("Starting git content source from {}", git_source.url);
+        return Ok(Some(Box::new(GitSource::new(git_source.clone()))));
+    }
+    let Some(markdown_path) = &CONFIG.markdown_path else {
+        info!("No markdown path configured, skipping markdown watcher");
+        return Ok(None);
+    };
+    let watch_path = PathBuf::from(markdown_path);
+    if !watch_path.exists() {
+        bail!("Markdown path {:?} does not exist", watch_path);
+    }
+    info!("Starting markdown watcher at {:?}", watch_path);
+    let synced_slugs = db::get_synced_slugs(conn).await?;
+    Ok(Some(Box::new(
+        MarkdownWatcher::new(watch_path).with_synced_slugs(synced_slugs),
+    )))
+}
+
 async fn init() -> Result<db::DatabaseConnection> {
     tracing_subscriber::fmt::Subscriber::builder()
         .compact()
@@ -136,3 +210,56 @@ async fn import_xlog_content(source: PathBuf, output: PathBuf, obj_output: Optio
     info!("Content import completed");
     Ok(())
 }
+
+async fn import_content(
+    format: ImportFormat,
+    source: PathBuf,
+    output: PathBuf,
+    obj_output: Option<PathBuf>,
+) -> Result<()> {
+    info!("Starting to import content from {:?} to {:?}", source, output);
+    match format {
+        ImportFormat::Xlog => suwen_markdown::importer::import_path(source, output, obj_output, XlogImporter).await?,
+        // 三者的 front matter 与目录结构由同一个导入器按文件识别
+        ImportFormat::Hugo | ImportFormat::Jekyll | ImportFormat::Hexo => {
+            suwen_markdown::importer::import_path(source, output, obj_output, SsgImporter).await?
+        }
+        ImportFormat::Wordpress => {
+            let comments = import_wordpress(source, output.clone(), obj_output).await?;
+            if comments > 0 {
+                info!(
+                    "Saved {} comments, run `suwen import-comments -s {}` after the articles are synced",
+                    comments,
+                    output.join(COMMENTS_FILE).display()
+                );
+            }
+        }
+    }
+    info!("Content import completed");
+    Ok(())
+}
+
+/// 评论挂在已同步的文章下，文章未同步时跳过对应评论，同步后重新执行即可补上，已导入的评论不会重复导入
+async fn import_comments(source: PathBuf) -> Result<()> {
+    let comments: Vec<ImportedComment> = serde_json::from_slice(&tokio::fs::read(&source).await?)?;
+    let sqlite_connection = init().await?;
+    let total = comments.len();
+    let imported = db::import_comments(&sqlite_connection, comments).await?;
+    info!("Imported {} new comments out of {} in the export", imported, total);
+    Ok(())
+}
+
+/// 清理后运行中的服务会在上传前重新确认清单中的文件仍然存在，无需重启
+async fn media_gc(dry_run: bool) -> Result<()> {
+    let sqlite_connection = init().await?;
+    let keys = suwen_api::collect_orphaned_media(&sqlite_connection, dry_run).await?;
+    for key in &keys {
+        println!("{}", key);
+    }
+    if dry_run {
+        info!("Found {} orphaned media files", keys.len());
+    } else {
+        info!("Deleted {} orphaned media files", keys.len());
+    }
+    Ok(())
+}